@@ -0,0 +1,6 @@
+#!/bin/sh
+# expects --seed=<seed> <params file> --value=<value>, without appended positional arguments
+[ "$#" = "3" ] || exit 1
+[ "$1" = "--seed=$CAMBRIAN_SEED" ] || exit 1
+[ "$3" = "--value=$(cat "$2")" ] || exit 1
+echo '{"objFuncVal":0.1}'
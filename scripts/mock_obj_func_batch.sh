@@ -0,0 +1,3 @@
+#!/bin/sh
+results=$(echo "$1" | grep -o '"id":[0-9]*' | sed 's/"id":\([0-9]*\)/{"id":\1,"objFuncVal":\1}/' | paste -sd, -)
+echo "[$results]"
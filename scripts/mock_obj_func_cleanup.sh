@@ -0,0 +1,4 @@
+#!/bin/sh
+trap 'echo "cleaned up" > "$1"; exit 1' TERM
+sleep 10 &
+wait
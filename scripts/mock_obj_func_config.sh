@@ -0,0 +1,7 @@
+#!/bin/sh
+# expects the path of the rendered config file as the only argument
+[ "$#" = "1" ] || exit 1
+[ "$1" = "$CAMBRIAN_CONFIG_FILE" ] || exit 1
+grep -qx "seed = $CAMBRIAN_SEED" "$1" || exit 1
+grep -Eqx "value = (true|false)" "$1" || exit 1
+echo '{"objFuncVal":0.1}'
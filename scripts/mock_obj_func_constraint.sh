@@ -0,0 +1,6 @@
+#!/bin/sh
+if [ "$1" = "true" ]; then
+    echo '{"objFuncVal":0.1,"constraintViolation":2.0}'
+else
+    echo '{"objFuncVal":0.5}'
+fi
@@ -0,0 +1,5 @@
+#!/bin/sh
+if [ "$1" = "true" ]; then
+    while :; do :; done
+fi
+echo '{"objFuncVal":0.1}'
@@ -0,0 +1,3 @@
+#!/bin/sh
+# dies from the signal given as first argument, as if it crashed or was killed from elsewhere
+kill -s "$1" $$
@@ -0,0 +1,10 @@
+#!/bin/sh
+# fails unless the evaluation context is passed in the environment, the seed is the fourth argument
+[ "$CAMBRIAN_OUT_DIR" = "$1" ] || exit 1
+[ "$EXTRA_VAR" = "$2" ] || exit 1
+[ "$CAMBRIAN_SEED" = "$4" ] || exit 1
+[ "$CAMBRIAN_SLOT" = "0" ] || exit 1
+[ -n "$CAMBRIAN_INDIVIDUAL_ID" ] || exit 1
+[ -z "$CAMBRIAN_SAMPLE_INDEX" ] || exit 1
+[ "${CAMBRIAN_DEADLINE%.*}" -ge "$(date +%s)" ] || exit 1
+echo '{"objFuncVal":0.1}'
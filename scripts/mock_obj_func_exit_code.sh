@@ -0,0 +1,5 @@
+#!/bin/sh
+# rejects individuals with odd seeds, fails transiently on the first attempt for all others
+[ $((CAMBRIAN_SEED % 2)) = 1 ] && exit 3
+[ -e attempted ] || { touch attempted; exit 75; }
+echo '{"objFuncVal":0.1}'
@@ -0,0 +1,5 @@
+#!/bin/sh
+echo 'loading model'
+echo 'warming up' >&2
+sleep 10
+echo '{"objFuncVal":0.1}'
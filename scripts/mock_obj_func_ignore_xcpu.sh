@@ -0,0 +1,7 @@
+#!/bin/sh
+# keeps running past the CPU time soft limit, until killed at the hard limit
+trap '' XCPU
+if [ "$1" = "true" ]; then
+    while :; do :; done
+fi
+echo '{"objFuncVal":0.1}'
@@ -0,0 +1,13 @@
+#!/bin/sh
+if [ "$2" = "0" ]; then
+    offset=0
+else
+    offset=10
+fi
+
+for step in 0 1 2; do
+    echo "{\"step\": $step, \"intermediate\": $((offset + 3 - step))}"
+    sleep 0.05
+done
+
+echo "{\"objFuncVal\": $offset}"
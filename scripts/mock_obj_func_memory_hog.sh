@@ -0,0 +1,5 @@
+#!/bin/sh
+if [ "$1" = "true" ]; then
+    head -c 300000000 /dev/zero | tail -c 300000000 > /dev/null || exit 1
+fi
+echo '{"objFuncVal":0.1}'
@@ -0,0 +1,5 @@
+#!/bin/sh
+echo 'loading model'
+echo 'CAMBRIAN_RESULT: {"objFuncVal":0.2}'
+echo '{"objFuncVal":0.1}'
+echo
@@ -0,0 +1,6 @@
+#!/bin/sh
+# writes a result file but fails transiently on the first attempt, writes none on the retry
+[ -e attempted ] && exit 0
+touch attempted
+echo '{"objFuncVal":0.3}' > "$CAMBRIAN_RESULT_FILE"
+exit 75
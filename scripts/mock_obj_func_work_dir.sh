@@ -0,0 +1,8 @@
+#!/bin/sh
+[ "$(pwd)" = "$CAMBRIAN_WORK_DIR" ] || exit 1
+echo "$1" > artifact.json
+if [ "$1" = "true" ]; then
+    echo '{"objFuncVal":1.0}'
+else
+    echo '{"objFuncVal":2.0}'
+fi
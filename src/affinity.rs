@@ -0,0 +1,155 @@
+use crate::error::Error;
+use crate::meta::CpuPinningConfig;
+use nix::sched::{sched_getaffinity, sched_setaffinity, CpuSet};
+use nix::unistd::{gettid, Pid};
+use std::io;
+
+// Splits the CPUs the calling thread may run on into the reserved CPUs and the CPU set of every
+// concurrency slot, taking them in ascending order
+pub fn partition(
+    cpu_pinning: &CpuPinningConfig,
+    num_slots: usize,
+) -> Result<(Vec<usize>, Vec<Vec<usize>>), Error> {
+    partition_cpus(&available_cpus()?, cpu_pinning, num_slots)
+}
+
+// Restores the affinity the thread had before it was pinned
+pub struct PinnedThread {
+    tid: Pid,
+    previous_cpu_set: CpuSet,
+}
+
+impl Drop for PinnedThread {
+    fn drop(&mut self) {
+        sched_setaffinity(self.tid, &self.previous_cpu_set).ok();
+    }
+}
+
+fn partition_cpus(
+    available_cpus: &[usize],
+    cpu_pinning: &CpuPinningConfig,
+    num_slots: usize,
+) -> Result<(Vec<usize>, Vec<Vec<usize>>), Error> {
+    let required = cpu_pinning.num_reserved_cpus + num_slots * cpu_pinning.cpus_per_slot;
+
+    if required > available_cpus.len() {
+        return Err(Error::NotEnoughCpus {
+            required,
+            available: available_cpus.len(),
+        });
+    }
+
+    let (reserved_cpus, slot_cpus) = available_cpus.split_at(cpu_pinning.num_reserved_cpus);
+
+    let slot_cpu_sets = slot_cpus
+        .chunks(cpu_pinning.cpus_per_slot)
+        .take(num_slots)
+        .map(<[usize]>::to_vec)
+        .collect();
+
+    Ok((reserved_cpus.to_vec(), slot_cpu_sets))
+}
+
+fn available_cpus() -> Result<Vec<usize>, Error> {
+    let cpu_set = sched_getaffinity(Pid::from_raw(0)).map_err(io::Error::from)?;
+
+    Ok((0..CpuSet::count())
+        .filter(|cpu| cpu_set.is_set(*cpu).unwrap_or(false))
+        .collect())
+}
+
+pub fn to_cpu_set(cpus: &[usize]) -> Result<CpuSet, Error> {
+    let mut cpu_set = CpuSet::new();
+
+    for cpu in cpus {
+        cpu_set.set(*cpu).map_err(io::Error::from)?;
+    }
+
+    Ok(cpu_set)
+}
+
+// Threads the pinned thread spawns inherit its CPUs. The thread id is kept rather than relying on
+// the guard being dropped on the same thread
+pub fn pin_current_thread(cpus: &[usize]) -> Result<PinnedThread, Error> {
+    let tid = gettid();
+    let previous_cpu_set = sched_getaffinity(tid).map_err(io::Error::from)?;
+
+    confine_current_thread(cpus)?;
+
+    Ok(PinnedThread {
+        tid,
+        previous_cpu_set,
+    })
+}
+
+// for threads that end before the CPUs would have to be given back
+pub fn confine_current_thread(cpus: &[usize]) -> Result<(), Error> {
+    sched_setaffinity(gettid(), &to_cpu_set(cpus)?).map_err(io::Error::from)?;
+    Ok(())
+}
+
+pub fn format_cpu_set(cpus: &[usize]) -> String {
+    cpus.iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_config(cpus_per_slot: usize, num_reserved_cpus: usize) -> CpuPinningConfig {
+        CpuPinningConfig {
+            cpus_per_slot,
+            num_reserved_cpus,
+        }
+    }
+
+    #[test]
+    fn partition() {
+        let (reserved, slots) =
+            partition_cpus(&[0, 1, 2, 3, 4, 6, 7], &make_config(2, 1), 3).unwrap();
+
+        assert_eq!(reserved, vec![0]);
+        assert_eq!(slots, vec![vec![1, 2], vec![3, 4], vec![6, 7]]);
+    }
+
+    #[test]
+    fn partition_leaves_surplus_cpus_unused() {
+        let (reserved, slots) = partition_cpus(&[0, 1, 2, 3], &make_config(1, 0), 2).unwrap();
+
+        assert!(reserved.is_empty());
+        assert_eq!(slots, vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn not_enough_cpus() {
+        assert!(matches!(
+            partition_cpus(&[0, 1, 2, 3], &make_config(2, 1), 2),
+            Err(Error::NotEnoughCpus {
+                required: 5,
+                available: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn unpin_on_drop() {
+        std::thread::spawn(|| {
+            let cpus = available_cpus().unwrap();
+            let pinned_thread = pin_current_thread(&cpus[..1]).unwrap();
+            assert_eq!(available_cpus().unwrap(), cpus[..1]);
+
+            drop(pinned_thread);
+            assert_eq!(available_cpus().unwrap(), cpus);
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_cpu_set(&[2, 3, 5]), "2,3,5");
+    }
+}
@@ -1,9 +1,13 @@
 pub(crate) use crate::crossover::Crossover;
+use crate::distance;
+use crate::meta::AlgoConfig;
 use crate::meta::MetaParamsSource;
 use crate::meta::MetaParamsWrapper;
+use crate::meta::ObjectiveTransform;
 use crate::meta_adapt;
 use crate::mutation;
 use crate::selection::{Selection, SelectionImpl};
+use crate::surrogate::KnnSurrogate;
 use crate::value::Value;
 use crate::{
     meta::{CrossoverParams, MutationParams},
@@ -16,7 +20,7 @@ use rand::rngs::StdRng;
 use rand::SeedableRng;
 use rand_distr::num_traits::ToPrimitive;
 use rand_distr::{Bernoulli, Distribution};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use tangram_finite::FiniteF64;
 
 const STATIC_PARAMS: StaticParams = StaticParams {
@@ -44,10 +48,17 @@ pub struct AlgoContext {
     individuals: BTreeMap<OrderingKey, IndContext>,
     initial_value: Value,
     initial_value_used: bool,
+    injected_values: VecDeque<Value>,
     crossover: Crossover,
     path_ctx: PathContext,
     rng: StdRng,
     next_id: usize,
+    id_step: usize,
+    crowding: bool,
+    rank_transform: bool,
+    maximize: bool,
+    surrogate: Option<KnnSurrogate>,
+    surrogate_num_candidates: usize,
     meta_params_override: Option<(CrossoverParams, MutationParams)>,
     static_params: StaticParams,
 }
@@ -88,25 +99,82 @@ impl AlgoContext {
             individual_sample_size,
             individuals: BTreeMap::default(),
             initial_value_used: false,
+            injected_values: VecDeque::default(),
             crossover: Crossover::new(),
             path_ctx,
             rng: StdRng::seed_from_u64(0),
             next_id: 0,
+            id_step: 1,
+            crowding: false,
+            rank_transform: false,
+            maximize: false,
+            surrogate: None,
+            surrogate_num_candidates: 1,
             static_params,
             meta_params_override,
         }
     }
+
+    pub fn for_island(
+        spec: Spec,
+        algo_config: &AlgoConfig,
+        explicit_init_value: Option<Value>,
+        island_idx: usize,
+    ) -> Self {
+        let mut algo_ctx = Self::new(
+            spec,
+            algo_config.individual_sample_size,
+            None,
+            explicit_init_value,
+        );
+
+        algo_ctx.crowding = algo_config.crowding;
+        algo_ctx.rank_transform = algo_config.objective.transform == ObjectiveTransform::Rank;
+        algo_ctx.maximize = algo_config.objective.maximize;
+
+        if let Some(num_candidates) = algo_config.surrogate_num_candidates {
+            algo_ctx.surrogate = Some(KnnSurrogate::new(algo_config.surrogate_num_neighbors));
+            algo_ctx.surrogate_num_candidates = num_candidates;
+        }
+
+        // ids are interleaved across islands so that they stay globally unique and the
+        // originating island can be recovered from the id alone
+        algo_ctx.next_id = island_idx;
+        algo_ctx.id_step = algo_config.num_islands;
+        algo_ctx.rng = StdRng::seed_from_u64(island_idx as u64);
+
+        // only the first island evaluates the initial value as is, the others start
+        // off with mutations of it
+        algo_ctx.initial_value_used = island_idx > 0;
+
+        algo_ctx
+    }
 }
 
+// feasible individuals (zero constraint violation) rank before infeasible ones, which are
+// ranked by constraint violation alone (Deb's rules)
 #[derive(Ord, Eq, PartialEq, PartialOrd, Clone, Debug)]
 struct OrderingKey {
+    constraint_violation: FiniteF64,
     obj_func_val: FiniteF64,
     id: usize,
 }
 
 impl OrderingKey {
     fn new(id: usize, obj_func_val: FiniteF64) -> Self {
-        Self { obj_func_val, id }
+        Self {
+            constraint_violation: FiniteF64::new(0.0).unwrap(),
+            obj_func_val,
+            id,
+        }
+    }
+
+    fn infeasible(id: usize, constraint_violation: FiniteF64) -> Self {
+        Self {
+            constraint_violation,
+            obj_func_val: FiniteF64::new(0.0).unwrap(),
+            id,
+        }
     }
 }
 
@@ -114,7 +182,45 @@ impl OrderingKey {
 enum IndState {
     PendingEval(Vec<FiniteF64>),
     Ready(Vec<FiniteF64>),
-    Final(FiniteF64),
+    Final(Vec<FiniteF64>),
+    // with the samples collected before the evaluation that turned out infeasible
+    Infeasible(FiniteF64, Vec<FiniteF64>),
+}
+
+impl IndState {
+    fn obj_func_vals(&self) -> &[FiniteF64] {
+        match self {
+            IndState::PendingEval(obj_func_vals)
+            | IndState::Ready(obj_func_vals)
+            | IndState::Final(obj_func_vals)
+            | IndState::Infeasible(_, obj_func_vals) => obj_func_vals,
+        }
+    }
+}
+
+// percentile ranks of objective function values among those of the current population, ties
+// counting half
+struct SampleRanks(Vec<FiniteF64>);
+
+impl SampleRanks {
+    fn new<'a>(obj_func_vals: impl Iterator<Item = &'a FiniteF64>) -> Self {
+        Self(obj_func_vals.copied().sorted().collect())
+    }
+
+    fn rank(&self, obj_func_val: FiniteF64) -> FiniteF64 {
+        let num_better = self.0.partition_point(|val| *val < obj_func_val);
+        let num_not_worse = self.0.partition_point(|val| *val <= obj_func_val);
+        FiniteF64::new((num_better + num_not_worse) as f64 / (2 * self.0.len()) as f64).unwrap()
+    }
+
+    fn summary(&self, obj_func_vals: &[FiniteF64]) -> FiniteF64 {
+        let ranks = obj_func_vals
+            .iter()
+            .map(|obj_func_val| self.rank(*obj_func_val))
+            .collect_vec();
+
+        summary_obj_func_val(&ranks)
+    }
 }
 
 #[derive(Debug)]
@@ -122,20 +228,53 @@ pub struct IndContext {
     pub id: usize,
     pub value: Value,
     pub meta_params_used: Option<MetaParamsWrapper>,
+    pub surrogate_prediction: Option<f64>,
+    pub raw_obj_func_vals: Vec<f64>,
     state: IndState,
 }
 
 impl IndContext {
+    // mean of the objective function values prior to any objective transform
+    pub fn raw_obj_func_val(&self) -> Option<f64> {
+        if self.raw_obj_func_vals.is_empty() {
+            None
+        } else {
+            Some(self.raw_obj_func_vals.iter().sum::<f64>() / self.raw_obj_func_vals.len() as f64)
+        }
+    }
+
+    // number of objective function values already obtained for the individual in stochastic mode
+    pub fn sample_index(&self) -> usize {
+        match self.state {
+            IndState::PendingEval(ref obj_func_vals) => obj_func_vals.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn is_pending_reeval(&self) -> bool {
+        matches!(self.state, IndState::PendingEval(ref obj_func_vals) if !obj_func_vals.is_empty())
+    }
+
     fn new(id: usize, value: Value, meta_params_used: Option<MetaParamsWrapper>) -> Self {
         Self {
             id,
             value,
             meta_params_used,
+            surrogate_prediction: None,
+            raw_obj_func_vals: Vec::default(),
             state: IndState::PendingEval(Vec::default()),
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Migrant {
+    pub value: Value,
+    pub meta_params_used: Option<MetaParamsWrapper>,
+    pub obj_func_vals: Vec<FiniteF64>,
+    pub raw_obj_func_vals: Vec<f64>,
+}
+
 fn summary_obj_func_val(obj_func_vals: &[FiniteF64]) -> FiniteF64 {
     let mean = obj_func_vals.iter().map(|val| val.get()).sum::<f64>() / obj_func_vals.len() as f64;
     FiniteF64::new(mean).unwrap()
@@ -144,7 +283,7 @@ fn summary_obj_func_val(obj_func_vals: &[FiniteF64]) -> FiniteF64 {
 impl AlgoContext {
     fn make_id(&mut self) -> usize {
         let result = self.next_id;
-        self.next_id += 1;
+        self.next_id += self.id_step;
         result
     }
 
@@ -156,11 +295,9 @@ impl AlgoContext {
             .map(|entry| entry.0)
             .next();
 
-        if let Some(key) = key {
-            Some(self.individuals.remove(&key.clone()).unwrap())
-        } else {
-            None
-        }
+        let ind_ctx = self.individuals.remove(&key?.clone());
+        self.rerank();
+        ind_ctx
     }
 
     fn try_reeval(&mut self) -> bool {
@@ -171,7 +308,18 @@ impl AlgoContext {
                 .sample(&mut self.rng)
     }
 
+    pub fn inject(&mut self, value: Value) {
+        self.path_ctx.add_nodes_for(&value);
+        self.injected_values.push_back(value);
+    }
+
     pub fn next_individual(&mut self) -> IndContext {
+        if let Some(value) = self.injected_values.pop_front() {
+            let id = self.make_id();
+            info!("Individual {}: Injected", id);
+            return IndContext::new(id, value, None);
+        }
+
         if self.try_reeval() {
             if let Some(mut ind_ctx) = self.extract_best_ready() {
                 let state = if let IndState::Ready(obj_func_vals) = ind_ctx.state {
@@ -189,20 +337,68 @@ impl AlgoContext {
             }
         }
 
-        let (value, meta_params_used) = if !self.initial_value_used {
+        let (value, meta_params_used, surrogate_prediction) = if !self.initial_value_used {
             self.initial_value_used = true;
-            (self.initial_value.clone(), None)
+            (self.initial_value.clone(), None, None)
         } else {
-            let (value, meta_params_wrapper) = self.create_offspring();
+            let (value, meta_params_wrapper, surrogate_prediction) =
+                self.create_screened_offspring();
 
-            (value, Some(meta_params_wrapper))
+            (value, Some(meta_params_wrapper), surrogate_prediction)
         };
 
         let id = self.make_id();
 
         info!("Individual {}: Created", id);
 
-        IndContext::new(id, value, meta_params_used)
+        let mut ind_ctx = IndContext::new(id, value, meta_params_used);
+        ind_ctx.surrogate_prediction = surrogate_prediction;
+        ind_ctx
+    }
+
+    fn create_screened_offspring(&mut self) -> (Value, MetaParamsWrapper, Option<f64>) {
+        let num_candidates = match self.surrogate {
+            Some(ref surrogate) if surrogate.is_ready() => self.surrogate_num_candidates,
+            _ => 1,
+        };
+
+        let candidates = (0..num_candidates)
+            .map(|_| self.create_offspring())
+            .collect_vec();
+
+        match self.surrogate {
+            Some(ref surrogate) if surrogate.is_ready() => {
+                // predictions are raw objective function values
+                let orient = |prediction: f64| {
+                    if self.maximize {
+                        -prediction
+                    } else {
+                        prediction
+                    }
+                };
+
+                let (value, meta_params_wrapper, prediction) = candidates
+                    .into_iter()
+                    .map(|(value, meta_params_wrapper)| {
+                        let prediction = surrogate.predict(&self.spec, &value);
+                        (value, meta_params_wrapper, prediction)
+                    })
+                    .min_by(|lhs, rhs| orient(lhs.2).total_cmp(&orient(rhs.2)))
+                    .unwrap();
+
+                trace!(
+                    "Selected most promising out of {} candidates, surrogate prediction: {}",
+                    num_candidates,
+                    prediction
+                );
+
+                (value, meta_params_wrapper, Some(prediction))
+            }
+            _ => {
+                let (value, meta_params_wrapper) = candidates.into_iter().next().unwrap();
+                (value, meta_params_wrapper, None)
+            }
+        }
     }
 
     fn create_offspring(&mut self) -> (Value, MetaParamsWrapper) {
@@ -295,13 +491,38 @@ impl AlgoContext {
         }
     }
 
-    fn summary_obj_func_val(&self, ind_state: &IndState) -> FiniteF64 {
-        match *ind_state {
-            IndState::PendingEval(ref obj_func_vals) | IndState::Ready(ref obj_func_vals) => {
-                summary_obj_func_val(obj_func_vals)
+    fn ordering_key(ind_ctx: &IndContext, sample_ranks: Option<&SampleRanks>) -> OrderingKey {
+        match ind_ctx.state {
+            IndState::Infeasible(constraint_violation, _) => {
+                OrderingKey::infeasible(ind_ctx.id, constraint_violation)
             }
-            IndState::Final(obj_func_val) => obj_func_val,
+            ref state => {
+                let obj_func_val = match sample_ranks {
+                    Some(sample_ranks) => sample_ranks.summary(state.obj_func_vals()),
+                    None => summary_obj_func_val(state.obj_func_vals()),
+                };
+
+                OrderingKey::new(ind_ctx.id, obj_func_val)
+            }
+        }
+    }
+
+    // ranks depend on the whole population, so all keys are recomputed whenever it changes
+    fn rerank(&mut self) {
+        if !self.rank_transform {
+            return;
         }
+
+        let sample_ranks = SampleRanks::new(
+            self.individuals
+                .values()
+                .flat_map(|ctx| ctx.state.obj_func_vals()),
+        );
+
+        self.individuals = std::mem::take(&mut self.individuals)
+            .into_values()
+            .map(|ctx| (Self::ordering_key(&ctx, Some(&sample_ranks)), ctx))
+            .collect();
     }
 
     fn transition_state(&self, state: IndState, obj_func_val: FiniteF64, id: usize) -> IndState {
@@ -316,7 +537,7 @@ impl AlgoContext {
                     id, summary_obj_func_val
                 );
 
-                IndState::Final(summary_obj_func_val)
+                IndState::Final(obj_func_vals)
             } else {
                 IndState::Ready(obj_func_vals)
             }
@@ -330,20 +551,22 @@ impl AlgoContext {
             return;
         }
 
-        let num_to_take = 10.min(self.individuals.len());
-
         let summary_obj_func_vals: Vec<f64> = self
             .individuals
             .values()
-            .take(num_to_take)
-            .map(|ctx| match ctx.state {
-                IndState::PendingEval(ref obj_func_vals) | IndState::Ready(ref obj_func_vals) => {
-                    summary_obj_func_val(obj_func_vals).get()
-                }
-                IndState::Final(obj_func_val) => obj_func_val.get(),
+            .map_while(|ctx| match ctx.state {
+                IndState::Infeasible(..) => None,
+                ref state => Some(summary_obj_func_val(state.obj_func_vals()).get()),
             })
+            .take(10)
             .collect();
 
+        let num_to_take = summary_obj_func_vals.len();
+
+        if num_to_take == 0 {
+            return;
+        }
+
         let mean: f64 = summary_obj_func_vals.iter().sum::<f64>() / num_to_take.to_f64().unwrap();
 
         info!(
@@ -364,27 +587,148 @@ impl AlgoContext {
                 obj_func_val.get()
             );
 
-            ind_ctx.state = self.transition_state(ind_ctx.state, obj_func_val, ind_ctx.id);
-
-            let ordering_key =
-                OrderingKey::new(ind_ctx.id, self.summary_obj_func_val(&ind_ctx.state));
-            self.individuals.insert(ordering_key, ind_ctx);
-
-            while self.individuals.len() > self.static_params.max_pop_size {
-                let key_to_remove = self.individuals.iter().next_back().unwrap().0.clone();
-                self.individuals.remove(&key_to_remove);
+            // the surrogate learns raw values, so that its predictions can be compared with them
+            if let (Some(ref mut surrogate), Some(&raw_obj_func_val)) =
+                (&mut self.surrogate, ind_ctx.raw_obj_func_vals.last())
+            {
+                surrogate.train(ind_ctx.value.clone(), raw_obj_func_val);
             }
 
+            let reevaluated = ind_ctx.is_pending_reeval();
+            ind_ctx.state = self.transition_state(ind_ctx.state, obj_func_val, ind_ctx.id);
+
+            self.insert_individual(ind_ctx, !reevaluated);
             self.log_top_obj_func_vals();
         } else {
             info!("Individual {}: value rejected", ind_ctx.id);
         }
     }
 
-    pub fn best_seen_final(&self) -> Option<(FiniteF64, &Value)> {
+    pub fn process_infeasible_eval(
+        &mut self,
+        mut ind_ctx: IndContext,
+        constraint_violation: FiniteF64,
+    ) {
+        info!(
+            "Individual {}: infeasible with constraint violation: {}",
+            ind_ctx.id,
+            constraint_violation.get()
+        );
+
+        let reevaluated = ind_ctx.is_pending_reeval();
+        let obj_func_vals = ind_ctx.state.obj_func_vals().to_vec();
+        ind_ctx.state = IndState::Infeasible(constraint_violation, obj_func_vals);
+        self.insert_individual(ind_ctx, !reevaluated);
+    }
+
+    // individuals back from re-evaluation already hold their place in the population, only new
+    // ones compete against their nearest neighbour
+    fn insert_individual(&mut self, ind_ctx: IndContext, new_individual: bool) {
+        let id = ind_ctx.id;
+        let crowding = self.crowding
+            && new_individual
+            && self.individuals.len() >= self.static_params.max_pop_size;
+
+        self.individuals
+            .insert(Self::ordering_key(&ind_ctx, None), ind_ctx);
+        self.rerank();
+
+        if crowding {
+            self.truncate_crowding(id);
+        }
+
+        while self.individuals.len() > self.static_params.max_pop_size {
+            let key_to_remove = self.individuals.iter().next_back().unwrap().0.clone();
+            self.individuals.remove(&key_to_remove);
+        }
+
+        self.rerank();
+    }
+
+    fn truncate_crowding(&mut self, id: usize) {
+        let (ordering_key, ind_ctx) = self
+            .individuals
+            .iter()
+            .find(|(key, _)| key.id == id)
+            .unwrap();
+
+        // the new individual competes against its nearest neighbour only, so that distinct
+        // niches survive truncation even if they are outperformed by another niche
+        let nearest_key = self
+            .individuals
+            .iter()
+            .filter(|(key, _)| key.id != id)
+            .map(|(key, ctx)| {
+                (
+                    key,
+                    distance::distance(&self.spec, &ind_ctx.value, &ctx.value),
+                )
+            })
+            .min_by(|lhs, rhs| lhs.1.total_cmp(&rhs.1))
+            .map(|(key, _)| key.clone())
+            .unwrap();
+
+        if *ordering_key < nearest_key {
+            trace!(
+                "Individual {}: replaces nearest neighbour {}",
+                id,
+                nearest_key.id
+            );
+            self.individuals.remove(&nearest_key);
+        } else {
+            trace!(
+                "Individual {}: discarded in favour of nearest neighbour {}",
+                id,
+                nearest_key.id
+            );
+            let ordering_key = ordering_key.clone();
+            self.individuals.remove(&ordering_key);
+        }
+    }
+
+    pub fn best_final_for_migration(&self) -> Option<Migrant> {
+        self.individuals.values().find_map(|ctx| {
+            if let IndState::Final(ref obj_func_vals) = ctx.state {
+                Some(Migrant {
+                    value: ctx.value.clone(),
+                    meta_params_used: ctx.meta_params_used.clone(),
+                    obj_func_vals: obj_func_vals.clone(),
+                    raw_obj_func_vals: ctx.raw_obj_func_vals.clone(),
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn receive_migrant(&mut self, migrant: Migrant) {
+        if self
+            .individuals
+            .values()
+            .any(|ctx| ctx.value == migrant.value)
+        {
+            return;
+        }
+
+        let id = self.make_id();
+
+        info!(
+            "Individual {}: received as migrant with objective function value: {}",
+            id,
+            summary_obj_func_val(&migrant.obj_func_vals).get()
+        );
+
+        let mut ind_ctx = IndContext::new(id, migrant.value, migrant.meta_params_used);
+        ind_ctx.state = IndState::Final(migrant.obj_func_vals);
+        ind_ctx.raw_obj_func_vals = migrant.raw_obj_func_vals;
+        self.insert_individual(ind_ctx, true);
+    }
+
+    // with the mean of the values, which unlike ranks can be compared across islands
+    pub fn best_seen_final(&self) -> Option<(FiniteF64, &IndContext)> {
         self.individuals.values().find_map(|ctx| {
-            if let IndState::Final(obj_func_val) = ctx.state {
-                Some((obj_func_val, &ctx.value))
+            if let IndState::Final(ref obj_func_vals) = ctx.state {
+                Some((summary_obj_func_val(obj_func_vals), ctx))
             } else {
                 None
             }
@@ -461,7 +805,7 @@ mod tests {
             assert_eq!(sut.next_individual().value.0, value::Node::Bool(false));
         }
 
-        assert_eq!(sut.best_seen_final(), None);
+        assert!(sut.best_seen_final().is_none());
     }
 
     #[test]
@@ -480,9 +824,9 @@ mod tests {
             assert_eq!(sut.next_individual().value.0, value::Node::Bool(true));
         }
 
-        if let Some((obj_func_val, value)) = sut.best_seen_final() {
+        if let Some((obj_func_val, ind_ctx)) = sut.best_seen_final() {
             assert_eq!(obj_func_val.get(), 0.1);
-            assert_eq!(*value, Value(value::Node::Bool(false)));
+            assert_eq!(ind_ctx.value, Value(value::Node::Bool(false)));
         } else {
             panic!()
         }
@@ -492,7 +836,7 @@ mod tests {
     fn best_seen_overtaken() {
         let mut sut = make_sut();
         sut.next_individual();
-        assert_eq!(sut.best_seen_final(), None);
+        assert!(sut.best_seen_final().is_none());
 
         let (ind_ctx, obj_func_val) = make_result(0, true, 0.2);
         sut.process_individual_eval(ind_ctx, obj_func_val);
@@ -509,10 +853,10 @@ mod tests {
 
         assert_eq!(sut.next_individual().value.0, value::Node::Bool(true));
 
-        let (obj_func_val, value) = sut.best_seen_final().unwrap();
+        let (obj_func_val, ind_ctx) = sut.best_seen_final().unwrap();
         assert_eq!(obj_func_val.get(), 0.1);
 
-        assert_eq!(*value, value::Value(value::Node::Bool(false)));
+        assert_eq!(ind_ctx.value, value::Value(value::Node::Bool(false)));
     }
 
     #[test]
@@ -542,6 +886,198 @@ mod tests {
         assert_eq!(sut.individuals.values().next().unwrap().id, 1); // the individual 0 was evicted
     }
 
+    #[test]
+    fn feasible_ranked_before_infeasible() {
+        let mut sut = make_sut();
+
+        for (id, constraint_violation) in [(0, 0.2), (1, 0.1)] {
+            let (ind_ctx, _) = make_result(id, true, 0.0);
+            sut.process_infeasible_eval(ind_ctx, FiniteF64::new(constraint_violation).unwrap());
+        }
+
+        assert!(sut.best_seen_final().is_none());
+
+        let (ind_ctx, obj_func_val) = make_result(2, false, 10.0);
+        sut.process_individual_eval(ind_ctx, obj_func_val);
+
+        let ids: Vec<usize> = sut.individuals.values().map(|ctx| ctx.id).collect();
+        assert_eq!(ids, vec![2, 1, 0]);
+
+        let (obj_func_val, ind_ctx) = sut.best_seen_final().unwrap();
+        assert_eq!(obj_func_val.get(), 10.0);
+        assert_eq!(ind_ctx.value.0, value::Node::Bool(false));
+    }
+
+    #[test]
+    fn infeasible_reeval_keeps_samples() {
+        let mut sut = make_sut();
+
+        let (mut ind_ctx, _) = make_result(0, true, 0.0);
+        ind_ctx.state = IndState::PendingEval(vec![FiniteF64::new(0.2).unwrap()]);
+        sut.process_infeasible_eval(ind_ctx, FiniteF64::new(0.1).unwrap());
+
+        assert_eq!(
+            sut.individuals.values().next().unwrap().state,
+            IndState::Infeasible(
+                FiniteF64::new(0.1).unwrap(),
+                vec![FiniteF64::new(0.2).unwrap()]
+            )
+        );
+    }
+
+    #[test]
+    fn crowding_replaces_nearest_neighbour() {
+        let static_params = StaticParams {
+            max_pop_size: 2,
+            ..STATIC_PARAMS
+        };
+
+        let spec = spec_util::from_yaml_str(
+            "
+        type: real
+        init: 0.0
+        scale: 1.0
+        ",
+        )
+        .unwrap();
+
+        let mut sut = AlgoContext::new_impl(
+            spec,
+            1,
+            Some((NEVER_CROSSOVER, ALWAYS_MUTATE)),
+            None,
+            static_params,
+        );
+        sut.crowding = true;
+
+        let make_real_result = |id: usize, value: f64, obj_func_val: f64| {
+            (
+                IndContext::new(id, Value(value::Node::Real(value)), None),
+                Some(FiniteF64::new(obj_func_val).unwrap()),
+            )
+        };
+
+        for (id, value, obj_func_val) in [(0, 0.0, 0.1), (1, 10.0, 0.5)] {
+            let (ind_ctx, obj_func_val) = make_real_result(id, value, obj_func_val);
+            sut.process_individual_eval(ind_ctx, obj_func_val);
+        }
+
+        // close to individual 0 and worse: discarded, even though better than individual 1
+        let (ind_ctx, obj_func_val) = make_real_result(2, 0.5, 0.2);
+        sut.process_individual_eval(ind_ctx, obj_func_val);
+
+        let ids: Vec<usize> = sut.individuals.values().map(|ctx| ctx.id).collect();
+        assert_eq!(ids, vec![0, 1]);
+
+        // close to individual 1 and better: replaces it
+        let (ind_ctx, obj_func_val) = make_real_result(3, 9.5, 0.4);
+        sut.process_individual_eval(ind_ctx, obj_func_val);
+
+        let ids: Vec<usize> = sut.individuals.values().map(|ctx| ctx.id).collect();
+        assert_eq!(ids, vec![0, 3]);
+    }
+
+    #[test]
+    fn crowding_spares_reevaluated_individuals() {
+        let static_params = StaticParams {
+            max_pop_size: 2,
+            ..STATIC_PARAMS
+        };
+
+        let spec = spec_util::from_yaml_str(
+            "
+        type: real
+        init: 0.0
+        scale: 1.0
+        ",
+        )
+        .unwrap();
+
+        let mut sut = AlgoContext::new_impl(
+            spec,
+            2,
+            Some((NEVER_CROSSOVER, ALWAYS_MUTATE)),
+            None,
+            static_params,
+        );
+        sut.crowding = true;
+
+        for (id, value, obj_func_val) in [(0, 0.0, 0.1), (1, 10.0, 0.05)] {
+            sut.process_individual_eval(
+                IndContext::new(id, Value(value::Node::Real(value)), None),
+                Some(FiniteF64::new(obj_func_val).unwrap()),
+            );
+        }
+
+        // back from re-evaluation while the population filled up, close to individual 1 and
+        // worse than it, but better than individual 0
+        let mut ind_ctx = IndContext::new(2, Value(value::Node::Real(9.5)), None);
+        ind_ctx.state = IndState::PendingEval(vec![FiniteF64::new(0.08).unwrap()]);
+        sut.process_individual_eval(ind_ctx, Some(FiniteF64::new(0.08).unwrap()));
+
+        let ids: Vec<usize> = sut.individuals.values().map(|ctx| ctx.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn surrogate_screening() {
+        let spec = spec_util::from_yaml_str(
+            "
+        type: real
+        init: 0.0
+        scale: 1.0
+        ",
+        )
+        .unwrap();
+
+        let mut sut = AlgoContext::new(spec, 1, Some((NEVER_CROSSOVER, ALWAYS_MUTATE)), None);
+        sut.surrogate = Some(KnnSurrogate::new(1));
+        sut.surrogate_num_candidates = 50;
+
+        let initial_individual = sut.next_individual();
+        assert_eq!(initial_individual.surrogate_prediction, None);
+
+        for (id, value, obj_func_val) in [(100, -10.0, -10.0), (101, 10.0, 10.0)] {
+            let mut ind_ctx = IndContext::new(id, Value(value::Node::Real(value)), None);
+            ind_ctx.raw_obj_func_vals.push(obj_func_val);
+            sut.process_individual_eval(ind_ctx, Some(FiniteF64::new(obj_func_val).unwrap()));
+        }
+
+        // out of 50 candidates, one closer to -10.0 than to 10.0 is chosen
+        let screened = sut.next_individual();
+        assert_approx_eq!(f64, screened.surrogate_prediction.unwrap(), -10.0);
+        assert!(matches!(screened.value.0, value::Node::Real(x) if x < 0.0));
+    }
+
+    #[test]
+    fn surrogate_screening_maximize() {
+        let spec = spec_util::from_yaml_str(
+            "
+        type: real
+        init: 0.0
+        scale: 1.0
+        ",
+        )
+        .unwrap();
+
+        let mut sut = AlgoContext::new(spec, 1, Some((NEVER_CROSSOVER, ALWAYS_MUTATE)), None);
+        sut.surrogate = Some(KnnSurrogate::new(1));
+        sut.surrogate_num_candidates = 50;
+        sut.maximize = true;
+        sut.next_individual();
+
+        for (id, value, raw_obj_func_val) in [(100, -10.0, -10.0), (101, 10.0, 10.0)] {
+            let mut ind_ctx = IndContext::new(id, Value(value::Node::Real(value)), None);
+            ind_ctx.raw_obj_func_vals.push(raw_obj_func_val);
+            sut.process_individual_eval(ind_ctx, Some(FiniteF64::new(-raw_obj_func_val).unwrap()));
+        }
+
+        // predictions stay raw, the highest one is chosen
+        let screened = sut.next_individual();
+        assert_approx_eq!(f64, screened.surrogate_prediction.unwrap(), 10.0);
+        assert!(matches!(screened.value.0, value::Node::Real(x) if x > 0.0));
+    }
+
     #[test]
     fn reeval() {
         let sample_size = 2;
@@ -594,6 +1130,7 @@ mod tests {
                 ..
             } if *obj_func_vals == vec![FiniteF64::new(0.2).unwrap()]
         ));
+        assert_eq!(next_individual.sample_index(), 1);
 
         // reevaluation result
         sut.process_individual_eval(next_individual, Some(FiniteF64::new(0.1).unwrap()));
@@ -605,9 +1142,9 @@ mod tests {
             IndContext {
                 id: 0,
                 value: Value(value::Node::Bool(true)),
-                state: IndState::Final(obj_func_val),
+                state: IndState::Final(ref obj_func_vals),
                 ..
-            } if obj_func_val.approx_eq(0.15, F64Margin::default())
+            } if summary_obj_func_val(obj_func_vals).approx_eq(0.15, F64Margin::default())
         ));
         assert_eq!(sut.individuals.len(), 2);
 
@@ -636,6 +1173,7 @@ mod tests {
                 ..
             } if *obj_func_vals == vec![]
         ));
+        assert_eq!(next_individual.sample_index(), 0);
 
         // comes back first, better than second, but worse than first
         sut.process_individual_eval(next_individual, Some(FiniteF64::new(0.25).unwrap()));
@@ -650,6 +1188,36 @@ mod tests {
         assert_eq!(ind_ids_in_ranking_order, vec![0, 2, 1]);
     }
 
+    #[test]
+    fn rank_transform_over_population() {
+        let make_final = |id: usize, obj_func_vals: [f64; 2]| {
+            let mut ind_ctx = IndContext::new(id, Value(value::Node::Bool(true)), None);
+            ind_ctx.state = IndState::Final(
+                obj_func_vals
+                    .into_iter()
+                    .map(|val| FiniteF64::new(val).unwrap())
+                    .collect(),
+            );
+            ind_ctx
+        };
+
+        let ranking_order = |rank_transform: bool| {
+            let mut sut = AlgoContext::new_impl(TRIVIAL_SPEC, 2, None, None, STATIC_PARAMS);
+            sut.rank_transform = rank_transform;
+
+            for (id, obj_func_vals) in [(0, [0.0, 100.0]), (1, [1.0, 2.0]), (2, [3.0, 4.0])] {
+                sut.insert_individual(make_final(id, obj_func_vals), true);
+            }
+
+            assert_eq!(sut.best_seen_final().unwrap().0.get(), 1.5);
+            sut.individuals.values().map(|ctx| ctx.id).collect_vec()
+        };
+
+        assert_eq!(ranking_order(false), vec![1, 2, 0]);
+        // the outlier only costs individual 0 one rank, instead of dominating its mean
+        assert_eq!(ranking_order(true), vec![1, 0, 2]);
+    }
+
     #[test]
     fn summary_obj_func_val_single_value() {
         let values = vec![FiniteF64::new(1.0).unwrap()];
@@ -0,0 +1,301 @@
+use crate::error::Error;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
+const PARAMS_PLACEHOLDER: &str = "params";
+const PARAMS_FILE_PLACEHOLDER: &str = "params_file";
+const PARAMS_PATH_PREFIX: &str = "params.";
+const SEED_PLACEHOLDER: &str = "seed";
+const BUDGET_PLACEHOLDER: &str = "budget";
+const INDIVIDUAL_ID_PLACEHOLDER: &str = "individual_id";
+const WORK_DIR_PLACEHOLDER: &str = "workdir";
+const CONFIG_FILE_PLACEHOLDER: &str = "config_file";
+const RESULT_FILE_PLACEHOLDER: &str = "result_file";
+
+// Values placeholders in objective function arguments are substituted with. Those that do not
+// apply to an evaluation are none, e.g. the seed in batch mode.
+#[derive(Clone, Copy)]
+pub struct ArgContext<'a> {
+    pub params: &'a serde_json::Value,
+    pub params_file: Option<&'a Path>,
+    pub seed: Option<u64>,
+    pub budget: Option<f64>,
+    pub individual_id: Option<usize>,
+    pub work_dir: Option<&'a Path>,
+    pub config_file: Option<&'a Path>,
+    pub result_file: Option<&'a Path>,
+}
+
+// Which of the placeholders that depend on the evaluation mode and options have a value in a
+// launch
+#[derive(Clone, Copy)]
+pub struct AvailablePlaceholders {
+    pub seed: bool,
+    pub budget: bool,
+    pub individual_id: bool,
+    pub work_dir: bool,
+    pub config_file: bool,
+    pub result_file: bool,
+}
+
+impl AvailablePlaceholders {
+    fn contains(&self, name: &str) -> bool {
+        match name {
+            SEED_PLACEHOLDER => self.seed,
+            BUDGET_PLACEHOLDER => self.budget,
+            INDIVIDUAL_ID_PLACEHOLDER => self.individual_id,
+            WORK_DIR_PLACEHOLDER => self.work_dir,
+            CONFIG_FILE_PLACEHOLDER => self.config_file,
+            RESULT_FILE_PLACEHOLDER => self.result_file,
+            // parameter paths depend on the value and can only be resolved per evaluation
+            _ => true,
+        }
+    }
+}
+
+fn is_placeholder(name: &str) -> bool {
+    matches!(
+        name,
+        PARAMS_PLACEHOLDER
+            | PARAMS_FILE_PLACEHOLDER
+            | SEED_PLACEHOLDER
+            | BUDGET_PLACEHOLDER
+            | INDIVIDUAL_ID_PLACEHOLDER
+            | WORK_DIR_PLACEHOLDER
+            | CONFIG_FILE_PLACEHOLDER
+            | RESULT_FILE_PLACEHOLDER
+    ) || name.starts_with(PARAMS_PATH_PREFIX)
+}
+
+// names of the placeholders in the argument, braces not enclosing a known placeholder are
+// taken literally
+fn placeholders(arg: &str) -> impl Iterator<Item = (usize, &str)> {
+    arg.match_indices('{').filter_map(move |(start, _)| {
+        let len = arg[start + 1..].find('}')?;
+        let name = &arg[start + 1..start + 1 + len];
+        is_placeholder(name).then_some((start, name))
+    })
+}
+
+pub fn has_placeholders(args: &[OsString]) -> bool {
+    args.iter()
+        .filter_map(|arg| arg.to_str())
+        .any(|arg| placeholders(arg).next().is_some())
+}
+
+pub fn uses_params_file(args: &[OsString]) -> bool {
+    args.iter()
+        .filter_map(|arg| arg.to_str())
+        .any(|arg| placeholders(arg).any(|(_, name)| name == PARAMS_FILE_PLACEHOLDER))
+}
+
+// names of the placeholders used in the texts that never have a value in the launch
+pub fn unavailable_placeholders<'a>(
+    texts: impl IntoIterator<Item = &'a str>,
+    available: &AvailablePlaceholders,
+) -> Vec<String> {
+    texts
+        .into_iter()
+        .flat_map(placeholders)
+        .map(|(_, name)| name)
+        .filter(|name| !available.contains(name))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn substitute(arg: &OsStr, ctx: &ArgContext) -> Result<OsString, Error> {
+    match arg.to_str() {
+        Some(arg) => substitute_str(arg, ctx).map(OsString::from),
+        None => Ok(arg.to_owned()),
+    }
+}
+
+pub fn substitute_str(arg: &str, ctx: &ArgContext) -> Result<String, Error> {
+    let mut result = String::new();
+    let mut pos = 0;
+
+    for (start, name) in placeholders(arg) {
+        // placeholders cannot overlap, but a brace inside one may look like the start of another
+        if start < pos {
+            continue;
+        }
+
+        result.push_str(&arg[pos..start]);
+        result.push_str(&resolve(name, ctx)?);
+        pos = start + name.len() + 2;
+    }
+
+    result.push_str(&arg[pos..]);
+
+    Ok(result)
+}
+
+fn resolve(name: &str, ctx: &ArgContext) -> Result<String, Error> {
+    let unresolvable = || Error::UnresolvablePlaceholder(name.to_string());
+
+    match name {
+        PARAMS_PLACEHOLDER => Ok(ctx.params.to_string()),
+        PARAMS_FILE_PLACEHOLDER => ctx
+            .params_file
+            .map(|path| path.display().to_string())
+            .ok_or_else(unresolvable),
+        SEED_PLACEHOLDER => ctx
+            .seed
+            .map(|seed| seed.to_string())
+            .ok_or_else(unresolvable),
+        BUDGET_PLACEHOLDER => ctx
+            .budget
+            .map(|budget| budget.to_string())
+            .ok_or_else(unresolvable),
+        INDIVIDUAL_ID_PLACEHOLDER => ctx
+            .individual_id
+            .map(|individual_id| individual_id.to_string())
+            .ok_or_else(unresolvable),
+        WORK_DIR_PLACEHOLDER => ctx
+            .work_dir
+            .map(|path| path.display().to_string())
+            .ok_or_else(unresolvable),
+        CONFIG_FILE_PLACEHOLDER => ctx
+            .config_file
+            .map(|path| path.display().to_string())
+            .ok_or_else(unresolvable),
+        RESULT_FILE_PLACEHOLDER => ctx
+            .result_file
+            .map(|path| path.display().to_string())
+            .ok_or_else(unresolvable),
+        _ => {
+            let path = &name[PARAMS_PATH_PREFIX.len()..];
+
+            let value = path
+                .split('.')
+                .try_fold(ctx.params, |value, key| match value {
+                    serde_json::Value::Object(map) => map.get(key),
+                    serde_json::Value::Array(items) => {
+                        key.parse::<usize>().ok().and_then(|idx| items.get(idx))
+                    }
+                    _ => None,
+                });
+
+            // optional and variant nodes are legitimately absent in some individuals
+            Ok(match value {
+                Some(serde_json::Value::String(value)) => value.clone(),
+                Some(value) => value.to_string(),
+                None => String::new(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn make_ctx(params: &serde_json::Value) -> ArgContext<'_> {
+        ArgContext {
+            params,
+            params_file: Some(Path::new("/tmp/params.json")),
+            seed: Some(7),
+            budget: None,
+            individual_id: Some(3),
+            work_dir: Some(Path::new("/out/evals/3-7")),
+            config_file: None,
+            result_file: Some(Path::new("/out/evals/3-7/result.json")),
+        }
+    }
+
+    #[test]
+    fn substitution() {
+        let params = json!({"optimizer": {"lr": 0.01, "name": "adam"}, "layers": [64, 32]});
+        let ctx = make_ctx(&params);
+
+        assert_eq!(substitute_str("--seed={seed}", &ctx).unwrap(), "--seed=7");
+        assert_eq!(
+            substitute_str("{workdir}/result.json", &ctx).unwrap(),
+            "/out/evals/3-7/result.json"
+        );
+        assert_eq!(
+            substitute_str("{params_file}", &ctx).unwrap(),
+            "/tmp/params.json"
+        );
+        assert_eq!(
+            substitute_str("{params.optimizer.lr}:{params.optimizer.name}", &ctx).unwrap(),
+            "0.01:adam"
+        );
+        assert_eq!(substitute_str("{params.layers.1}", &ctx).unwrap(), "32");
+        assert_eq!(
+            substitute_str("{params.optimizer}", &ctx).unwrap(),
+            r#"{"lr":0.01,"name":"adam"}"#
+        );
+        assert_eq!(substitute_str("id={individual_id}", &ctx).unwrap(), "id=3");
+        assert_eq!(
+            substitute_str("--out={result_file}", &ctx).unwrap(),
+            "--out=/out/evals/3-7/result.json"
+        );
+        assert_eq!(
+            substitute(OsStr::new("{seed}"), &ctx).unwrap(),
+            OsString::from("7")
+        );
+    }
+
+    #[test]
+    fn unknown_placeholders_are_literal() {
+        let params = json!({});
+        let ctx = make_ctx(&params);
+
+        assert_eq!(
+            substitute_str(r#"{"a": {"b": 1}} {x}"#, &ctx).unwrap(),
+            r#"{"a": {"b": 1}} {x}"#
+        );
+        assert_eq!(substitute_str("{{seed}}", &ctx).unwrap(), "{7}");
+    }
+
+    #[test]
+    fn absent_params_are_empty() {
+        let params = json!({"a": 1});
+        let ctx = make_ctx(&params);
+
+        assert_eq!(substitute_str("--b={params.b}", &ctx).unwrap(), "--b=");
+        assert_eq!(substitute_str("{params.a.b}", &ctx).unwrap(), "");
+    }
+
+    #[test]
+    fn unresolvable() {
+        let params = json!({"a": 1});
+        let ctx = make_ctx(&params);
+
+        assert!(matches!(
+            substitute_str("{budget}", &ctx),
+            Err(Error::UnresolvablePlaceholder(_))
+        ));
+    }
+
+    #[test]
+    fn unavailable() {
+        let available = AvailablePlaceholders {
+            seed: false,
+            budget: false,
+            individual_id: false,
+            work_dir: true,
+            config_file: false,
+            result_file: true,
+        };
+
+        assert_eq!(
+            unavailable_placeholders(
+                ["--seed={seed}", "{workdir}/{params.x}", "{budget}:{params}"],
+                &available
+            ),
+            vec!["seed", "budget"]
+        );
+        assert!(unavailable_placeholders(["{result_file}", "{x}"], &available).is_empty());
+    }
+
+    #[test]
+    fn detection() {
+        assert!(has_placeholders(&["--seed={seed}".into()]));
+        assert!(!has_placeholders(&["--verbose".into(), "{x}".into()]));
+        assert!(uses_params_file(&["-c".into(), "{params_file}".into()]));
+        assert!(!uses_params_file(&["{params}".into()]));
+    }
+}
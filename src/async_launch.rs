@@ -1,54 +1,134 @@
+use crate::affinity;
+use crate::control::{ControlRequest, ControlServer};
 use crate::controller::start_controller;
 use crate::detailed_report::DetailedReportItem;
 use crate::error::Error;
-use crate::message::Command;
+use crate::message::{Command, Report};
 use crate::meta::{AlgoConfig, AsyncObjectiveFunction};
 use crate::result::FinalReport;
 use crate::spec::Spec;
-use futures::channel::mpsc::{Receiver, Sender};
-use futures::channel::oneshot;
-use futures::StreamExt;
+use crate::termination::TerminationCriterion;
+use futures::channel::mpsc::{self, Receiver, Sender, UnboundedSender};
+use futures::{FutureExt, StreamExt};
 
-#[allow(clippy::too_many_arguments)]
 pub async fn launch<F: AsyncObjectiveFunction>(
+    spec: Spec,
+    obj_func: F,
+    algo_config: AlgoConfig,
+    cmd_recv: Receiver<Command>,
+    detailed_report_sender: Sender<DetailedReportItem>,
+    termination_criterion: TerminationCriterion,
+    explicit_init_value: Option<serde_json::Value>,
+) -> Result<FinalReport, Error> {
+    // without a receiver, all reports are dropped
+    let (report_sender, _) = mpsc::unbounded();
+
+    launch_with_reports(
+        spec,
+        obj_func,
+        algo_config,
+        cmd_recv,
+        detailed_report_sender,
+        report_sender,
+        termination_criterion,
+        explicit_init_value,
+    )
+    .await
+}
+
+// All progress events are sent to report_sender. It is unbounded, so that a slow receiver never
+// holds up the optimization, at the cost of buffering the events it has not taken yet
+#[allow(clippy::too_many_arguments)]
+pub async fn launch_with_reports<F: AsyncObjectiveFunction>(
+    spec: Spec,
+    obj_func: F,
+    algo_config: AlgoConfig,
+    cmd_recv: Receiver<Command>,
+    detailed_report_sender: Sender<DetailedReportItem>,
+    report_sender: UnboundedSender<Report>,
+    termination_criterion: TerminationCriterion,
+    explicit_init_value: Option<serde_json::Value>,
+) -> Result<FinalReport, Error> {
+    // the runtime is the caller's, so the reserved CPUs are only kept out of the slots
+    let slot_cpu_sets = algo_config
+        .cpu_pinning
+        .as_ref()
+        .map(|cpu_pinning| affinity::partition(cpu_pinning, algo_config.num_concurrent))
+        .transpose()?
+        .map(|(_, slot_cpu_sets)| slot_cpu_sets);
+
+    launch_with_slot_cpu_sets(
+        spec,
+        obj_func,
+        algo_config,
+        cmd_recv,
+        detailed_report_sender,
+        report_sender,
+        termination_criterion,
+        explicit_init_value,
+        slot_cpu_sets,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn launch_with_slot_cpu_sets<F: AsyncObjectiveFunction>(
     spec: Spec,
     obj_func: F,
     algo_config: AlgoConfig,
     mut cmd_recv: Receiver<Command>,
     detailed_report_sender: Sender<DetailedReportItem>,
-    max_num_eval: Option<usize>,
-    target_obj_func_val: Option<f64>,
+    report_sender: UnboundedSender<Report>,
+    termination_criterion: TerminationCriterion,
     explicit_init_value: Option<serde_json::Value>,
+    slot_cpu_sets: Option<Vec<Vec<usize>>>,
 ) -> Result<FinalReport, Error> {
-    let mut abort_sig_sender_holder: Option<oneshot::Sender<()>>;
-    let (abort_sig_sender, abort_signal_recv) = oneshot::channel();
-    abort_sig_sender_holder = Some(abort_sig_sender);
+    obj_func.validate(&algo_config)?;
+
+    let (control_request_sender, control_request_recv) = mpsc::unbounded();
+
+    let control_server = algo_config
+        .control_socket
+        .as_deref()
+        .map(ControlServer::bind)
+        .transpose()?;
+
+    let serve_control_socket = async {
+        match &control_server {
+            Some(control_server) => control_server.serve(control_request_sender.clone()).await,
+            None => futures::future::pending().await,
+        }
+    };
 
     let controller = start_controller(
         algo_config,
         spec,
         obj_func,
-        abort_signal_recv,
+        control_request_recv,
         detailed_report_sender,
-        max_num_eval,
-        target_obj_func_val,
+        report_sender,
+        termination_criterion,
         explicit_init_value,
+        slot_cpu_sets,
     );
 
-    tokio::pin!(controller);
+    tokio::pin!(controller, serve_control_socket);
 
     loop {
         tokio::select! {
             cmd = cmd_recv.next() => {
-                if let Some(Command::Terminate) = cmd {
-                    abort_sig_sender_holder.take().map(|sender| sender.send(()).ok());
+                if let Some(command) = cmd {
+                    control_request_sender.unbounded_send(ControlRequest { command, reply: None }).ok();
                 } else {
                     return Err(Error::ClientHungUp);
                 }
             }
             res = &mut controller => {
+                // lets connection handlers send the responses to the last commands
+                serve_control_socket.as_mut().now_or_never();
                 return res;
             }
+            _ = &mut serve_control_socket => (),
         }
     }
 }
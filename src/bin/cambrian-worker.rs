@@ -0,0 +1,100 @@
+mod common;
+
+use anyhow::{Context, Result};
+use cambrian::distributed::{self, Endpoint};
+use cambrian::worker;
+use clap::Parser;
+use common::ProcessArgs;
+use log::{info, LevelFilter};
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Evaluates objective functions for a cambrian coordinator", long_about = None)]
+struct Args {
+    obj_func_program: OsString,
+    obj_func_program_args: Vec<OsString>,
+
+    /// Address of the coordinator started with --listen, host:port or unix:<path>
+    #[arg(long)]
+    connect: String,
+
+    /// Set number of objective function processes to run concurrently
+    #[arg(long, default_value_t = 1)]
+    capacity: usize,
+
+    /// Set path of the run config file, only its env and exitCodes sections apply
+    #[arg(short = 'c', long)]
+    config_file: Option<PathBuf>,
+
+    /// Set path of the output directory for working directories and archived output
+    #[arg(short, long)]
+    out_dir: Option<PathBuf>,
+
+    /// Keep the working directories and archived output of the evaluations with the highest values
+    #[arg(long)]
+    maximize: bool,
+
+    /// Set a timeout for individual objective function evaluations
+    #[arg(short = 'k', long)]
+    kill_obj_func_after: Option<String>,
+
+    #[command(flatten)]
+    process: ProcessArgs,
+
+    /// Enable logging
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+fn init_logger(args: &Args) {
+    let level_filter = if args.verbose {
+        LevelFilter::Info
+    } else {
+        LevelFilter::Error
+    };
+
+    env_logger::Builder::new()
+        .filter_level(level_filter)
+        .format_module_path(false)
+        .format_target(false)
+        .init();
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    init_logger(&args);
+
+    let run_config = common::load_run_config(args.config_file.as_deref())?;
+
+    // shared by successive workers, so an existing one is reused
+    if let Some(ref out_dir) = args.out_dir {
+        info!("Creating output directory: {}", out_dir.display());
+        fs::create_dir_all(out_dir).context("Unable to create output directory")?;
+    }
+
+    let obj_func_def = common::make_obj_func_def(
+        args.obj_func_program.clone(),
+        args.obj_func_program_args.clone(),
+        common::parse_kill_obj_func_after(&args.kill_obj_func_after)?,
+        &args.process,
+        args.out_dir.as_deref(),
+        &run_config,
+        args.maximize,
+    )?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    runtime.block_on(worker::run(
+        &Endpoint::from(args.connect.as_str()),
+        args.capacity,
+        distributed::DEFAULT_HEARTBEAT_INTERVAL,
+        obj_func_def,
+    ))?;
+
+    Ok(())
+}
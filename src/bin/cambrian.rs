@@ -1,32 +1,61 @@
+mod common;
+
 use anyhow::{Context, Result};
+use cambrian::control;
+use cambrian::coordinator::Coordinator;
+use cambrian::distributed::{self, Endpoint};
 use cambrian::error::{Error, ProcOutputWithObjFuncArg};
-use cambrian::meta::AlgoConfig;
+use cambrian::message::{Command, Response};
+use cambrian::meta::{
+    AlgoConfig, MigrationTopology, DEFAULT_PRUNING_MIN_NUM_REPORTS, DEFAULT_REDUCTION_FACTOR,
+};
+use cambrian::process::ExitCodes;
 use cambrian::result::FinalReport;
+use cambrian::run_config::RunConfig;
 use cambrian::spec::Spec;
 use cambrian::sync_launch::DetailedReportingFileInfo;
-use cambrian::termination::TerminationCriterion;
-use cambrian::{meta::AlgoConfigBuilder, process::ObjFuncProcessDef, spec_util, sync_launch};
-use clap::Parser;
+use cambrian::termination::{self, TerminationCriterion};
+use cambrian::{meta::AlgoConfigBuilder, spec_util, sync_launch};
+use clap::{Parser, Subcommand, ValueEnum};
+use common::{parse_duration, ProcessArgs};
 use log::{info, LevelFilter};
-use parse_duration::parse::parse;
 use std::fmt::Write;
 use std::path::Path;
-use std::{ffi::OsString, fs, path::PathBuf, time::Duration};
+use std::{ffi::OsString, fs, path::PathBuf};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    obj_func_program: OsString,
+    #[command(subcommand)]
+    subcommand: Option<SubcommandArgs>,
+
+    #[arg(required_unless_present = "listen")]
+    obj_func_program: Option<OsString>,
     obj_func_program_args: Vec<OsString>,
 
     /// Set path of the spec file
-    #[arg(short = 's', long)]
-    spec_file: PathBuf,
+    #[arg(short = 's', long, required = true)]
+    spec_file: Option<PathBuf>,
+
+    /// Set path of the run config file
+    #[arg(short = 'c', long)]
+    config_file: Option<PathBuf>,
 
     /// Set path of the output directory
     #[arg(short, long)]
     out_dir: Option<PathBuf>,
 
+    /// Accept commands from "cambrian ctl" through the Unix domain socket <OUT_DIR>/control.sock
+    #[arg(long, requires = "out_dir")]
+    control_socket: bool,
+
     /// Overwrite output directory if it already exists
     #[arg(short, long)]
     force: bool,
@@ -39,10 +68,34 @@ struct Args {
     #[arg(short, long)]
     target_obj_func_val: Option<f64>,
 
+    /// Maximize the objective function instead of minimizing it
+    #[arg(long)]
+    maximize: bool,
+
     /// Set duration after which to terminate the optimization
     #[arg(long)]
     terminate_after: Option<String>,
 
+    /// Terminate after this many objective function evaluations without improvement of the best seen value
+    #[arg(long)]
+    no_improvement_evals: Option<usize>,
+
+    /// Set minimum change of the best seen value that counts as improvement for --no-improvement-evals
+    #[arg(long, requires = "no_improvement_evals")]
+    no_improvement_min_delta: Option<f64>,
+
+    /// Terminate if the best seen value has not improved for this duration
+    #[arg(long)]
+    no_improvement_for: Option<String>,
+
+    /// Terminate when this expression holds, e.g. "evals(500) and (target(0.01) or no_improvement(200))". Combined with the other termination options by OR
+    #[arg(long)]
+    terminate_when: Option<String>,
+
+    /// On termination, wait up to this duration for in-flight evaluations to complete instead of aborting them. A second SIGINT aborts them right away
+    #[arg(long)]
+    drain_grace_period: Option<String>,
+
     /// Disable graceful termination on SIGINT
     #[arg(long)]
     no_signal_result: bool,
@@ -51,14 +104,90 @@ struct Args {
     #[arg(long)]
     num_concurrent: Option<usize>,
 
+    /// Evaluate on cambrian-worker processes connecting to this address, host:port or unix:<path>, instead of running the objective function locally. Set --num-concurrent to their total capacity
+    #[arg(long, conflicts_with_all = [
+        "obj_func_program", "kill_signal", "kill_grace_period", "limit_address_space", "limit_cpu_time",
+        "limit_open_files", "limit_core_file_size", "work_dirs", "archive_output", "config_template",
+        "config_base", "result_source", "result_prefix", "cpus_per_slot", "batch_size", "pruning_quantile",
+    ])]
+    listen: Option<String>,
+
     /// Set a timeout for individual objective function evaluations
     #[arg(short = 'k', long)]
     kill_obj_func_after: Option<String>,
 
+    #[command(flatten)]
+    process: ProcessArgs,
+
+    /// Pin each concurrency slot to this many CPUs, applied to the objective function processes run in it
+    #[arg(long)]
+    cpus_per_slot: Option<usize>,
+
+    /// Reserve this many CPUs for cambrian itself, which are not assigned to any slot
+    #[arg(long, requires = "cpus_per_slot")]
+    reserved_cpus: Option<usize>,
+
+    /// Evaluate this many individuals per objective function process, passed as one JSON array
+    #[arg(long, conflicts_with_all = ["config_template", "config_base"])]
+    batch_size: Option<usize>,
+
     /// Sample size for stochastic mode
     #[arg(long)]
     sample_size: Option<usize>,
 
+    /// Set number of islands (independently evolving subpopulations)
+    #[arg(long)]
+    num_islands: Option<usize>,
+
+    /// Set number of accepted evaluations between migrations of best individuals across islands
+    #[arg(long)]
+    migration_interval: Option<usize>,
+
+    /// Set topology along which individuals migrate across islands
+    #[arg(long, value_enum)]
+    migration_topology: Option<MigrationTopologyArg>,
+
+    /// Reuse results of previously evaluated identical individuals instead of re-evaluating them,
+    /// in stochastic mode only across runs with --eval-cache-file
+    #[arg(long)]
+    eval_cache: bool,
+
+    /// Set path of a file to persist the evaluation cache in across runs (implies --eval-cache)
+    #[arg(long)]
+    eval_cache_file: Option<PathBuf>,
+
+    /// Pre-screen this many candidate offspring with a surrogate model and only evaluate the most promising one
+    #[arg(long)]
+    surrogate_candidates: Option<usize>,
+
+    /// Set number of nearest neighbors the surrogate model bases its predictions on
+    #[arg(long)]
+    surrogate_neighbors: Option<usize>,
+
+    /// Enable multi-fidelity mode: set lowest budget passed to the objective function after the seed
+    #[arg(long, requires = "max_budget")]
+    min_budget: Option<f64>,
+
+    /// Enable multi-fidelity mode: set full budget passed to the objective function after the seed
+    #[arg(long, requires = "min_budget")]
+    max_budget: Option<f64>,
+
+    /// Set factor by which the number of individuals is reduced from one budget to the next
+    #[arg(long, requires = "min_budget")]
+    reduction_factor: Option<usize>,
+
+    /// Enable early pruning: abort evaluations whose intermediate report is worse than this quantile of those of other evaluations at the same step
+    #[arg(long)]
+    pruning_quantile: Option<f64>,
+
+    /// Set minimum number of intermediate reports at a step before evaluations can be pruned at that step
+    #[arg(long, requires = "pruning_quantile")]
+    pruning_min_reports: Option<usize>,
+
+    /// Preserve diversity by letting new individuals only compete against their nearest neighbour
+    #[arg(long)]
+    crowding: bool,
+
     /// Explicit initial guess
     #[arg(long)]
     initial_guess: Option<String>,
@@ -68,6 +197,85 @@ struct Args {
     verbose: bool,
 }
 
+#[derive(ValueEnum, Clone, Debug)]
+enum MigrationTopologyArg {
+    Ring,
+    Random,
+}
+
+impl From<MigrationTopologyArg> for MigrationTopology {
+    fn from(arg: MigrationTopologyArg) -> Self {
+        match arg {
+            MigrationTopologyArg::Ring => MigrationTopology::Ring,
+            MigrationTopologyArg::Random => MigrationTopology::Random,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum SubcommandArgs {
+    /// Control a running optimization started with --control-socket
+    Ctl(CtlArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct CtlArgs {
+    /// Output directory of the running optimization
+    out_dir: PathBuf,
+
+    #[command(subcommand)]
+    command: CtlCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum CtlCommand {
+    /// Terminate the optimization as on SIGINT
+    Terminate,
+    /// Stop scheduling new evaluations, in-flight ones are completed
+    Pause,
+    /// Resume scheduling evaluations
+    Resume,
+    /// Change the number of concurrent evaluations
+    SetNumConcurrent { num_concurrent: usize },
+    /// Evaluate this JSON value next
+    Inject { value: String },
+    /// Print best seen value, evaluation counts and in-flight individuals as JSON
+    Status,
+}
+
+impl TryFrom<CtlCommand> for Command {
+    type Error = anyhow::Error;
+
+    fn try_from(command: CtlCommand) -> Result<Self> {
+        Ok(match command {
+            CtlCommand::Terminate => Command::Terminate,
+            CtlCommand::Pause => Command::Pause,
+            CtlCommand::Resume => Command::Resume,
+            CtlCommand::SetNumConcurrent { num_concurrent } => {
+                Command::SetNumConcurrent { num_concurrent }
+            }
+            CtlCommand::Inject { value } => Command::Inject {
+                value: serde_json::from_str(&value)
+                    .context("Failed to parse value JSON. Check validity.")?,
+            },
+            CtlCommand::Status => Command::Status,
+        })
+    }
+}
+
+fn ctl(args: CtlArgs) -> Result<()> {
+    let socket_path = args.out_dir.join(control::CONTROL_SOCKET_FILE_NAME);
+
+    match control::send(&socket_path, &args.command.try_into()?)? {
+        Response::Ok => Ok(()),
+        Response::Status(status) => {
+            println!("{}", serde_json::to_string_pretty(&status)?);
+            Ok(())
+        }
+        Response::Error { message } => Err(anyhow::anyhow!(message)),
+    }
+}
+
 fn init_logger(args: &Args) {
     let level_filter = if args.verbose {
         LevelFilter::Info
@@ -82,19 +290,25 @@ fn init_logger(args: &Args) {
         .init();
 }
 
-fn load_spec(args: &Args) -> Result<Spec> {
-    let spec_file_display = args.spec_file.display();
+fn load_spec(spec_file: &Path) -> Result<Spec> {
+    let spec_file_display = spec_file.display();
     let spec_ctx = |op| format!("Unable to {} spec_file: {}", op, &spec_file_display);
 
     info!("Reading spec file: {}", spec_file_display);
-    let spec_str = fs::read_to_string(&args.spec_file).with_context(|| spec_ctx("read"))?;
+    let spec_str = fs::read_to_string(spec_file).with_context(|| spec_ctx("read"))?;
     let spec = spec_util::from_yaml_str(&spec_str).with_context(|| spec_ctx("parse"))?;
     Ok(spec)
 }
 
-fn make_algo_conf(args: &Args) -> Result<AlgoConfig> {
+fn make_algo_conf(args: &Args, run_config: &RunConfig) -> Result<AlgoConfig> {
     let mut algo_config_builder = AlgoConfigBuilder::new();
 
+    algo_config_builder.objective(run_config.objective.clone());
+
+    if args.maximize {
+        algo_config_builder.maximize(true);
+    }
+
     if let Some(num_concurrent) = args.num_concurrent {
         algo_config_builder.num_concurrent(num_concurrent);
     }
@@ -103,6 +317,67 @@ fn make_algo_conf(args: &Args) -> Result<AlgoConfig> {
         algo_config_builder.individual_sample_size(sample_size);
     }
 
+    if let Some(num_islands) = args.num_islands {
+        algo_config_builder.num_islands(num_islands);
+    }
+
+    if let Some(migration_interval) = args.migration_interval {
+        algo_config_builder.migration_interval(migration_interval);
+    }
+
+    if let Some(ref migration_topology) = args.migration_topology {
+        algo_config_builder.migration_topology(migration_topology.clone().into());
+    }
+
+    algo_config_builder.crowding(args.crowding);
+    algo_config_builder.eval_cache(args.eval_cache);
+
+    if let (Some(min_budget), Some(max_budget)) = (args.min_budget, args.max_budget) {
+        algo_config_builder.fidelity(
+            min_budget,
+            max_budget,
+            args.reduction_factor.unwrap_or(DEFAULT_REDUCTION_FACTOR),
+        );
+    }
+
+    if let Some(pruning_quantile) = args.pruning_quantile {
+        algo_config_builder.pruning(
+            pruning_quantile,
+            args.pruning_min_reports
+                .unwrap_or(DEFAULT_PRUNING_MIN_NUM_REPORTS),
+        );
+    }
+
+    if let Some(ref drain_grace_period) = args.drain_grace_period {
+        algo_config_builder.drain_grace_period(
+            parse_duration(drain_grace_period).context("Unable to parse \"drain_grace_period\"")?,
+        );
+    }
+
+    if let Some(cpus_per_slot) = args.cpus_per_slot {
+        algo_config_builder.cpu_pinning(cpus_per_slot, args.reserved_cpus.unwrap_or(0));
+    }
+
+    if let Some(batch_size) = args.batch_size {
+        algo_config_builder.batch_size(batch_size);
+    }
+
+    if let Some(surrogate_candidates) = args.surrogate_candidates {
+        algo_config_builder.surrogate_num_candidates(surrogate_candidates);
+    }
+
+    if let Some(surrogate_neighbors) = args.surrogate_neighbors {
+        algo_config_builder.surrogate_num_neighbors(surrogate_neighbors);
+    }
+
+    if let Some(ref eval_cache_file) = args.eval_cache_file {
+        algo_config_builder.eval_cache_file(eval_cache_file.clone());
+    }
+
+    if let (true, Some(out_dir)) = (args.control_socket, &args.out_dir) {
+        algo_config_builder.control_socket(out_dir.join(control::CONTROL_SOCKET_FILE_NAME));
+    }
+
     algo_config_builder.build().context("invalid input")
 }
 
@@ -124,34 +399,32 @@ fn assemble_termination_criteria(args: &Args) -> Result<Vec<TerminationCriterion
         termination_criteria.push(TerminationCriterion::TerminateAfter(terminate_after));
     }
 
-    if !args.no_signal_result {
-        termination_criteria.push(TerminationCriterion::Signal)
+    if let Some(no_improvement_evals) = args.no_improvement_evals {
+        termination_criteria.push(TerminationCriterion::NoImprovement {
+            evals: no_improvement_evals,
+            min_delta: args.no_improvement_min_delta.unwrap_or(0.0),
+        });
     }
 
-    Ok(termination_criteria)
-}
+    if let Some(ref no_improvement_for) = args.no_improvement_for {
+        let no_improvement_for =
+            parse_duration(no_improvement_for).context("Unable to parse \"no_improvement_for\"")?;
 
-fn make_obj_func_def(
-    obj_func_program: OsString,
-    obj_func_program_args: Vec<OsString>,
-    kill_obj_func_after: Option<String>,
-) -> Result<ObjFuncProcessDef> {
-    let kill_obj_func_after = kill_obj_func_after
-        .as_ref()
-        .map(|kill_after| {
-            parse_duration(kill_after).context("Unable to parse \"kill objective function after\"")
-        })
-        .transpose()?;
+        termination_criteria.push(TerminationCriterion::NoImprovementFor(no_improvement_for));
+    }
 
-    Ok(ObjFuncProcessDef::new(
-        obj_func_program,
-        obj_func_program_args,
-        kill_obj_func_after,
-    ))
-}
+    if let Some(ref terminate_when) = args.terminate_when {
+        termination_criteria.push(
+            termination::parse_expr(terminate_when)
+                .context("Unable to parse \"terminate_when\"")?,
+        );
+    }
+
+    if !args.no_signal_result {
+        termination_criteria.push(TerminationCriterion::Signal)
+    }
 
-fn parse_duration(value: &str) -> Result<Duration> {
-    parse(value).with_context(|| format!("Unable to parse duration from value \"{}\"", value))
+    Ok(termination_criteria)
 }
 
 fn process_report(report: FinalReport, out_dir: &Option<PathBuf>) -> Result<()> {
@@ -180,11 +453,10 @@ fn dump_diagnostic_files(
     write_file(
         &dump_info.failed_obj_func_arg_file_path,
         "failed objective function arguments",
-        format!(
-            "{:?} {}",
-            proc_info.obj_func_arg.as_os_str(),
-            proc_info.seed
-        )
+        match proc_info.seed {
+            Some(seed) => format!("{:?} {}", proc_info.obj_func_arg.as_os_str(), seed),
+            None => format!("{:?}", proc_info.obj_func_arg.as_os_str()),
+        }
         .as_bytes(),
     )?;
 
@@ -334,11 +606,16 @@ Best seen value can be found in file: {}",
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+
+    if let Some(SubcommandArgs::Ctl(ctl_args)) = args.subcommand.take() {
+        return ctl(ctl_args);
+    }
 
     init_logger(&args);
 
-    let algo_config = make_algo_conf(&args)?;
+    let run_config = common::load_run_config(args.config_file.as_deref())?;
+    let algo_config = make_algo_conf(&args, &run_config)?;
     let termination_criteria = assemble_termination_criteria(&args)?;
 
     if let Some(out_dir) = &args.out_dir {
@@ -347,28 +624,65 @@ fn main() -> Result<()> {
 
     let detailed_reporting_file_info = make_detailed_reporting_file_info(&args);
 
-    let spec = load_spec(&args)?;
-    let obj_func_def = make_obj_func_def(
-        args.obj_func_program,
-        args.obj_func_program_args,
-        args.kill_obj_func_after,
+    let spec = load_spec(
+        args.spec_file
+            .as_deref()
+            .expect("spec file is required without a subcommand"),
     )?;
 
     let explicit_init_value_json = args
         .initial_guess
-        .map(|json_str| serde_json::from_str(&json_str))
+        .as_ref()
+        .map(|json_str| serde_json::from_str(json_str))
         .transpose()
         .context("Failed to parse initial guess JSON. Check validity.")?;
 
-    let result = sync_launch::launch_with_async_obj_func(
-        spec,
-        obj_func_def,
-        algo_config,
-        termination_criteria,
-        explicit_init_value_json,
-        false,
-        detailed_reporting_file_info.as_ref(),
-    );
+    let kill_obj_func_after = common::parse_kill_obj_func_after(&args.kill_obj_func_after)?;
+
+    let result = match args.listen {
+        Some(ref listen) => {
+            // the workers run the processes, with their own config file
+            if !run_config.env.is_empty() || run_config.exit_codes != ExitCodes::default() {
+                anyhow::bail!(
+                    "env and exitCodes of the config file are set on the workers with --listen"
+                );
+            }
+
+            let coordinator = Coordinator::bind(
+                &Endpoint::from(listen.as_str()),
+                distributed::DEFAULT_HEARTBEAT_TIMEOUT,
+                kill_obj_func_after,
+            )?;
+            info!("Listening for workers on {}", coordinator.endpoint());
+
+            sync_launch::launch_with_async_obj_func(
+                spec,
+                coordinator,
+                algo_config,
+                termination_criteria,
+                explicit_init_value_json,
+                false,
+                detailed_reporting_file_info.as_ref(),
+            )
+        }
+        None => sync_launch::launch_with_async_obj_func(
+            spec,
+            common::make_obj_func_def(
+                args.obj_func_program.clone().unwrap_or_default(),
+                args.obj_func_program_args.clone(),
+                kill_obj_func_after,
+                &args.process,
+                args.out_dir.as_deref(),
+                &run_config,
+                algo_config.objective.maximize,
+            )?,
+            algo_config,
+            termination_criteria,
+            explicit_init_value_json,
+            false,
+            detailed_reporting_file_info.as_ref(),
+        ),
+    };
 
     let diagnostic_info = extract_diagnostic_info(&args.out_dir, &result);
 
@@ -0,0 +1,248 @@
+// Objective function process options shared by cambrian and cambrian-worker
+use anyhow::{Context, Result};
+use cambrian::config_template::ConfigFileDef;
+use cambrian::output_archive::OutputArchive;
+use cambrian::process::{ObjFuncProcessDef, ResultSource};
+use cambrian::run_config::{self, RunConfig};
+use cambrian::work_dir::WorkDirs;
+use clap::{Args, ValueEnum};
+use log::info;
+use nix::sys::signal::Signal;
+use parse_duration::parse::parse;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{self, Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Args, Debug)]
+pub struct ProcessArgs {
+    /// Set signal sent to objective function processes to stop them, e.g. SIGTERM or SIGINT. SIGKILL follows after the kill grace period
+    #[arg(long)]
+    kill_signal: Option<String>,
+
+    /// Set duration objective function processes are given to exit after the kill signal before they are killed with SIGKILL
+    #[arg(long)]
+    kill_grace_period: Option<String>,
+
+    /// Limit the address space of objective function processes, e.g. 4G. Evaluations exceeding it are rejected
+    #[arg(long)]
+    limit_address_space: Option<String>,
+
+    /// Limit the CPU time of objective function processes, e.g. 10min. Evaluations exceeding it are rejected
+    #[arg(long)]
+    limit_cpu_time: Option<String>,
+
+    /// Limit the number of files objective function processes can open. Evaluations exceeding it are rejected
+    #[arg(long)]
+    limit_open_files: Option<u64>,
+
+    /// Limit the size of core dumps of objective function processes, e.g. 0 to disable them
+    #[arg(long)]
+    limit_core_file_size: Option<String>,
+
+    /// Run each objective function process in a fresh working directory under <OUT_DIR>/evals, exported as CAMBRIAN_WORK_DIR
+    #[arg(long, requires = "out_dir")]
+    work_dirs: bool,
+
+    /// Only keep the working directories of the best this many evaluations and of failed ones
+    #[arg(long, requires = "work_dirs")]
+    keep_best_work_dirs: Option<usize>,
+
+    /// Archive the gzipped stdout and stderr of each evaluation under <OUT_DIR>/logs
+    #[arg(long, requires = "out_dir")]
+    archive_output: bool,
+
+    /// Only archive the last this many bytes of each stream, e.g. 1M
+    #[arg(long, requires = "archive_output")]
+    archive_max_size: Option<String>,
+
+    /// Only keep the archived output of the best this many evaluations and of failed ones
+    #[arg(long, requires = "archive_output")]
+    keep_best_archives: Option<usize>,
+
+    /// Render this template file with substituted placeholders for each evaluation, passed as {config_file} and CAMBRIAN_CONFIG_FILE
+    #[arg(long)]
+    config_template: Option<PathBuf>,
+
+    /// Merge the parameters into this JSON, YAML or TOML file for each evaluation, passed as {config_file} and CAMBRIAN_CONFIG_FILE
+    #[arg(long, conflicts_with = "config_template")]
+    config_base: Option<PathBuf>,
+
+    /// Take the result of objective function processes from the whole stdout, its last non-empty line, its last line starting with --result-prefix, or a JSON file written to the path passed as {result_file} and CAMBRIAN_RESULT_FILE
+    #[arg(long, value_enum, default_value = "stdout")]
+    result_source: ResultSourceArg,
+
+    /// Prefix of the result line, stripped before parsing it
+    #[arg(long, default_value = "CAMBRIAN_RESULT:")]
+    result_prefix: String,
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum ResultSourceArg {
+    Stdout,
+    LastLine,
+    Prefix,
+    File,
+}
+
+pub fn load_run_config(config_file: Option<&Path>) -> Result<RunConfig> {
+    match config_file {
+        Some(config_file) => {
+            let config_file_display = config_file.display();
+            let config_ctx = |op| format!("Unable to {} config_file: {}", op, &config_file_display);
+
+            info!("Reading config file: {}", config_file_display);
+            let config_str = fs::read_to_string(config_file).with_context(|| config_ctx("read"))?;
+            let run_config =
+                run_config::from_yaml_str(&config_str).with_context(|| config_ctx("parse"))?;
+            Ok(run_config)
+        }
+        None => Ok(RunConfig::default()),
+    }
+}
+
+pub fn parse_kill_obj_func_after(kill_obj_func_after: &Option<String>) -> Result<Option<Duration>> {
+    kill_obj_func_after
+        .as_ref()
+        .map(|kill_after| {
+            parse_duration(kill_after).context("Unable to parse \"kill objective function after\"")
+        })
+        .transpose()
+}
+
+pub fn make_obj_func_def(
+    obj_func_program: OsString,
+    obj_func_program_args: Vec<OsString>,
+    kill_obj_func_after: Option<Duration>,
+    args: &ProcessArgs,
+    out_dir: Option<&Path>,
+    run_config: &RunConfig,
+    maximize: bool,
+) -> Result<ObjFuncProcessDef> {
+    let mut obj_func_def =
+        ObjFuncProcessDef::new(obj_func_program, obj_func_program_args, kill_obj_func_after);
+
+    if let Some(ref kill_signal) = args.kill_signal {
+        obj_func_def.kill_signal = parse_signal(kill_signal)?;
+    }
+
+    if let Some(ref kill_grace_period) = args.kill_grace_period {
+        obj_func_def.kill_grace_period =
+            parse_duration(kill_grace_period).context("Unable to parse \"kill_grace_period\"")?;
+    }
+
+    if let Some(ref limit_address_space) = args.limit_address_space {
+        obj_func_def.resource_limits.address_space_bytes = Some(
+            parse_size(limit_address_space).context("Unable to parse \"limit_address_space\"")?,
+        );
+    }
+
+    if let Some(ref limit_cpu_time) = args.limit_cpu_time {
+        let limit_cpu_time =
+            parse_duration(limit_cpu_time).context("Unable to parse \"limit_cpu_time\"")?;
+
+        // the limit is enforced with a resolution of seconds
+        obj_func_def.resource_limits.cpu_time_secs = Some(limit_cpu_time.as_secs().max(1));
+    }
+
+    obj_func_def.resource_limits.open_files = args.limit_open_files;
+
+    if let Some(ref limit_core_file_size) = args.limit_core_file_size {
+        obj_func_def.resource_limits.core_file_size_bytes = Some(
+            parse_size(limit_core_file_size).context("Unable to parse \"limit_core_file_size\"")?,
+        );
+    }
+
+    obj_func_def.env = run_config
+        .env
+        .iter()
+        .map(|(key, value)| (key.into(), value.into()))
+        .collect();
+    obj_func_def.exit_codes = run_config.exit_codes.clone();
+
+    if let Some(out_dir) = out_dir {
+        obj_func_def.out_dir =
+            Some(path::absolute(out_dir).context("Unable to resolve output directory")?);
+    }
+
+    if let (true, Some(out_dir)) = (args.work_dirs, out_dir) {
+        obj_func_def.work_dirs = Some(WorkDirs::new(
+            &out_dir.join("evals"),
+            args.keep_best_work_dirs,
+            maximize,
+        )?);
+    }
+
+    obj_func_def.result_source = match args.result_source {
+        ResultSourceArg::Stdout => ResultSource::Stdout,
+        ResultSourceArg::LastLine => ResultSource::LastLine,
+        ResultSourceArg::Prefix => ResultSource::PrefixedLine(args.result_prefix.clone()),
+        ResultSourceArg::File => ResultSource::File,
+    };
+
+    if let (true, Some(out_dir)) = (args.archive_output, out_dir) {
+        let max_bytes = args
+            .archive_max_size
+            .as_ref()
+            .map(|max_size| parse_size(max_size).context("Unable to parse \"archive_max_size\""))
+            .transpose()?;
+
+        obj_func_def.output_archive = Some(OutputArchive::new(
+            &out_dir.join("logs"),
+            args.keep_best_archives,
+            max_bytes.map(|max_bytes| max_bytes as usize),
+            maximize,
+        )?);
+    }
+
+    if let Some(ref config_template) = args.config_template {
+        obj_func_def.config_file = Some(ConfigFileDef::from_template_file(config_template)?);
+    }
+
+    if let Some(ref config_base) = args.config_base {
+        obj_func_def.config_file = Some(ConfigFileDef::from_base_file(config_base)?);
+    }
+
+    Ok(obj_func_def)
+}
+
+// number of bytes with an optional binary unit suffix, e.g. 512M
+fn parse_size(value: &str) -> Result<u64> {
+    let (number, multiplier) = match value.trim().char_indices().last() {
+        Some((idx, unit)) if unit.is_ascii_alphabetic() => {
+            let exponent = match unit.to_ascii_uppercase() {
+                'K' => 1,
+                'M' => 2,
+                'G' => 3,
+                'T' => 4,
+                _ => anyhow::bail!("Unknown size unit in value \"{}\"", value),
+            };
+
+            (&value.trim()[..idx], 1024u64.pow(exponent))
+        }
+        _ => (value.trim(), 1),
+    };
+
+    let number: u64 = number
+        .parse()
+        .with_context(|| format!("Unable to parse size from value \"{}\"", value))?;
+
+    number
+        .checked_mul(multiplier)
+        .with_context(|| format!("Size too large: \"{}\"", value))
+}
+
+fn parse_signal(value: &str) -> Result<Signal> {
+    let signal_name = if value.starts_with("SIG") {
+        value.to_string()
+    } else {
+        format!("SIG{}", value)
+    };
+
+    Signal::from_str(&signal_name).with_context(|| format!("Unknown signal \"{}\"", value))
+}
+
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    parse(value).with_context(|| format!("Unable to parse duration from value \"{}\"", value))
+}
@@ -0,0 +1,216 @@
+use crate::arg_template::{self, ArgContext};
+use crate::error::Error;
+use std::fs;
+use std::path::Path;
+
+pub const CONFIG_FILE_ENV_VAR: &str = "CAMBRIAN_CONFIG_FILE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfigTemplate {
+    // text with placeholders, substituted as in the objective function arguments
+    Text(String),
+    // base document the value is merged into
+    Merge {
+        base: serde_json::Value,
+        format: ConfigFormat,
+    },
+}
+
+// Config file rendered for each evaluation, for objective functions that take their parameters
+// from a file instead of JSON
+#[derive(Debug, Clone)]
+pub struct ConfigFileDef {
+    pub template: ConfigTemplate,
+    // name of the rendered file
+    pub file_name: String,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Result<Self, Error> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(Self::Json),
+            Some("yaml") | Some("yml") => Ok(Self::Yaml),
+            Some("toml") => Ok(Self::Toml),
+            _ => Err(Error::UnsupportedConfigFormat(path.to_owned())),
+        }
+    }
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn read_file(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map_err(|err| Error::UnableToReadConfigTemplate {
+        path: path.to_owned(),
+        source: err,
+    })
+}
+
+impl ConfigFileDef {
+    pub fn from_template_file(path: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            template: ConfigTemplate::Text(read_file(path)?),
+            file_name: file_name_of(path),
+        })
+    }
+
+    // the format is derived from the file extension
+    pub fn from_base_file(path: &Path) -> Result<Self, Error> {
+        let format = ConfigFormat::from_path(path)?;
+        let content = read_file(path)?;
+        let base = match format {
+            ConfigFormat::Json => serde_json::from_str(&content).ok(),
+            ConfigFormat::Yaml => serde_yaml::from_str(&content).ok(),
+            ConfigFormat::Toml => toml::from_str(&content).ok(),
+        }
+        .ok_or_else(|| Error::InvalidConfigBase(path.to_owned()))?;
+
+        Ok(Self {
+            template: ConfigTemplate::Merge { base, format },
+            file_name: file_name_of(path),
+        })
+    }
+
+    pub(crate) fn render(&self, arg_ctx: &ArgContext) -> Result<String, Error> {
+        match &self.template {
+            ConfigTemplate::Text(text) => arg_template::substitute_str(text, arg_ctx),
+            ConfigTemplate::Merge { base, format } => {
+                let mut merged = base.clone();
+                merge(&mut merged, arg_ctx.params);
+
+                match format {
+                    ConfigFormat::Json => Ok(serde_json::to_string_pretty(&merged)?),
+                    ConfigFormat::Yaml => Ok(serde_yaml::to_string(&merged)?),
+                    // tables must come after plain values, which toml::Value takes care of
+                    ConfigFormat::Toml => toml::Value::try_from(merged)
+                        .and_then(|value| toml::to_string(&value))
+                        .map_err(|err| Error::UnableToRenderConfigFile(err.to_string())),
+                }
+            }
+        }
+    }
+}
+
+// maps are merged recursively, everything else in the overlay replaces the base
+fn merge(base: &mut serde_json::Value, overlay: &serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, overlay_value) in overlay {
+                match base.get_mut(key) {
+                    Some(base_value) => merge(base_value, overlay_value),
+                    None => {
+                        base.insert(key.clone(), overlay_value.clone());
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn make_arg_ctx(params: &serde_json::Value) -> ArgContext<'_> {
+        ArgContext {
+            params,
+            params_file: None,
+            seed: Some(3),
+            budget: None,
+            individual_id: Some(1),
+            work_dir: None,
+            config_file: None,
+            result_file: None,
+        }
+    }
+
+    fn make_merge_def(base: serde_json::Value, format: ConfigFormat) -> ConfigFileDef {
+        ConfigFileDef {
+            template: ConfigTemplate::Merge { base, format },
+            file_name: "config".to_string(),
+        }
+    }
+
+    #[test]
+    fn text() {
+        let sut = ConfigFileDef {
+            template: ConfigTemplate::Text(
+                "[optimizer]\nlr = {params.optimizer.lr}\nseed = {seed}\n".to_string(),
+            ),
+            file_name: "config.ini".to_string(),
+        };
+
+        let params = json!({"optimizer": {"lr": 0.1}});
+
+        assert_eq!(
+            sut.render(&make_arg_ctx(&params)).unwrap(),
+            "[optimizer]\nlr = 0.1\nseed = 3\n"
+        );
+    }
+
+    #[test]
+    fn merge_yaml() {
+        let base = json!({"optimizer": {"lr": 0.5, "name": "adam"}, "epochs": 10});
+        let sut = make_merge_def(base, ConfigFormat::Yaml);
+
+        let params = json!({"optimizer": {"lr": 0.1}});
+        let rendered = sut.render(&make_arg_ctx(&params)).unwrap();
+
+        let result: serde_json::Value = serde_yaml::from_str(&rendered).unwrap();
+        assert_eq!(
+            result,
+            json!({"optimizer": {"lr": 0.1, "name": "adam"}, "epochs": 10})
+        );
+    }
+
+    #[test]
+    fn merge_toml() {
+        let base = toml::from_str("[optimizer]\nname = \"adam\"\n").unwrap();
+        let sut = make_merge_def(base, ConfigFormat::Toml);
+
+        let params = json!({"optimizer": {"lr": 0.1}, "epochs": 10});
+        let rendered = sut.render(&make_arg_ctx(&params)).unwrap();
+
+        let result: serde_json::Value = toml::from_str(&rendered).unwrap();
+        assert_eq!(
+            result,
+            json!({"optimizer": {"lr": 0.1, "name": "adam"}, "epochs": 10})
+        );
+    }
+
+    #[test]
+    fn merge_replaces_non_maps() {
+        let mut base = json!({"layers": [64, 64], "name": "base"});
+        merge(&mut base, &json!({"layers": [32]}));
+
+        assert_eq!(base, json!({"layers": [32], "name": "base"}));
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("a/config.yml")).unwrap(),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("config.toml")).unwrap(),
+            ConfigFormat::Toml
+        );
+        assert!(matches!(
+            ConfigFormat::from_path(Path::new("config.ini")),
+            Err(Error::UnsupportedConfigFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::distributed;
+use crate::error::Error;
+use crate::message::{Command, Response};
+use futures::channel::mpsc::UnboundedSender;
+use futures::channel::oneshot;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use log::warn;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+
+pub const CONTROL_SOCKET_FILE_NAME: &str = "control.sock";
+
+pub(crate) struct ControlRequest {
+    pub command: Command,
+    // none for commands that do not expect a response
+    pub reply: Option<oneshot::Sender<Response>>,
+}
+
+// Unix domain socket taking one JSON command per line and answering each with one JSON
+// response line
+pub(crate) struct ControlServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlServer {
+    pub(crate) fn bind(path: &Path) -> Result<Self, Error> {
+        distributed::remove_stale_socket(path);
+
+        let listener =
+            UnixListener::bind(path).map_err(|err| Error::UnableToCreateControlSocket {
+                path: path.to_owned(),
+                source: err,
+            })?;
+
+        Ok(Self {
+            listener,
+            path: path.to_owned(),
+        })
+    }
+
+    // connections are served within this future, so that they end along with it
+    pub(crate) async fn serve(&self, request_sender: UnboundedSender<ControlRequest>) {
+        let mut connections = FuturesUnordered::new();
+
+        loop {
+            tokio::select! {
+                accepted = self.listener.accept() => match accepted {
+                    Ok((stream, _)) => connections.push(handle_connection(stream, request_sender.clone())),
+                    Err(err) => warn!("Unable to accept control connection: {}", err),
+                },
+                Some(()) = connections.next() => (),
+            }
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+async fn handle_connection(
+    stream: tokio::net::UnixStream,
+    request_sender: UnboundedSender<ControlRequest>,
+) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = tokio::io::BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match serde_json::from_str(&line) {
+            Ok(command) => {
+                let (reply, reply_recv) = oneshot::channel();
+                let request = ControlRequest {
+                    command,
+                    reply: Some(reply),
+                };
+
+                if request_sender.unbounded_send(request).is_err() {
+                    break;
+                }
+
+                match reply_recv.await {
+                    Ok(response) => response,
+                    Err(_) => break,
+                }
+            }
+            Err(err) => Response::Error {
+                message: format!("invalid command: {}", err),
+            },
+        };
+
+        let mut response_line = serde_json::to_vec(&response).unwrap();
+        response_line.push(b'\n');
+
+        if writer.write_all(&response_line).await.is_err() {
+            break;
+        }
+    }
+}
+
+// sends a single command to a running optimization and waits for the response
+pub fn send(socket_path: &Path, command: &Command) -> Result<Response, Error> {
+    let connect_err = |err| Error::UnableToConnectToControlSocket {
+        path: socket_path.to_owned(),
+        source: err,
+    };
+
+    let mut stream = UnixStream::connect(socket_path).map_err(connect_err)?;
+
+    let mut request_line = serde_json::to_vec(command)?;
+    request_line.push(b'\n');
+    stream.write_all(&request_line)?;
+
+    let mut response_line = String::new();
+    BufReader::new(stream).read_line(&mut response_line)?;
+
+    serde_json::from_str(&response_line)
+        .map_err(|_| Error::InvalidControlMessage(response_line.trim_end().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_format() {
+        let command: Command =
+            serde_json::from_str(r#"{"command":"setNumConcurrent","numConcurrent":4}"#).unwrap();
+        assert!(matches!(
+            command,
+            Command::SetNumConcurrent { num_concurrent: 4 }
+        ));
+
+        assert_eq!(
+            serde_json::to_string(&Response::Error {
+                message: "oops".to_string()
+            })
+            .unwrap(),
+            r#"{"result":"error","message":"oops"}"#
+        );
+    }
+}
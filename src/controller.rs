@@ -1,19 +1,32 @@
-use crate::algorithm::AlgoContext;
 use crate::algorithm::IndContext;
+use crate::control::ControlRequest;
 use crate::detailed_report::DetailedReportItem;
 use crate::error::Error;
+use crate::eval_cache::EvalCache;
+use crate::fidelity::SuccessiveHalving;
+use crate::island::Archipelago;
+use crate::message::{Command, InFlightIndividual, Report, Response, Status};
+use crate::objective::ObjectiveTransformer;
+use crate::pruning::{IntermediateReporter, Pruner};
 use crate::spec::Spec;
+use crate::termination::{TerminationCriterion, TerminationExpr};
 use crate::value_util;
 use crate::{
-    meta::{AlgoConfig, AsyncObjectiveFunction},
+    meta::{
+        AlgoConfig, AsyncObjectiveFunction, BatchItem, EvalContext, ObjFuncResult, RejectionReason,
+    },
     result::FinalReport,
 };
-use futures::channel::mpsc::Sender;
-use futures::channel::oneshot;
+use futures::channel::mpsc::{Sender, UnboundedReceiver, UnboundedSender};
 use futures::stream::FuturesUnordered;
 use futures::SinkExt;
+use futures::StreamExt;
 use futures::TryStreamExt;
+use futures_timer::Delay;
+use itertools::Itertools;
 use log::info;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tangram_finite::FiniteF64;
 
@@ -22,11 +35,12 @@ pub async fn start_controller<F: AsyncObjectiveFunction>(
     algo_config: AlgoConfig,
     spec: Spec,
     obj_func: F,
-    mut in_abort_signal_recv: oneshot::Receiver<()>,
+    mut control_request_recv: UnboundedReceiver<ControlRequest>,
     mut detailed_report_sender: Sender<DetailedReportItem>,
-    max_num_eval: Option<usize>,
-    target_obj_func_val: Option<f64>,
+    report_sender: UnboundedSender<Report>,
+    termination_criterion: TerminationCriterion,
     explicit_init_value_json: Option<serde_json::Value>,
+    slot_cpu_sets: Option<Vec<Vec<usize>>>,
 ) -> Result<FinalReport, Error> {
     let start_ts = Instant::now();
 
@@ -34,169 +48,813 @@ pub async fn start_controller<F: AsyncObjectiveFunction>(
 
     let mut seed_mgr = SeedManager::new();
 
+    let mut slot_mgr = SlotManager::new(algo_config.num_concurrent, slot_cpu_sets);
+
     let (abort_signal_sender, out_abort_signal_recv) = async_broadcast::broadcast::<()>(1);
     let mut count_accepted = 0usize;
     let mut count_rejected = 0usize;
+    let mut count_pruned = 0usize;
+    let mut count_infeasible = 0usize;
+    let mut count_limit_exceeded = 0usize;
+    let mut count_rejected_by_exit_code = 0usize;
+    let mut count_retries = 0usize;
 
     let explicit_init_value = explicit_init_value_json
         .map(|json_val| value_util::from_json_value(&json_val, &spec))
         .transpose()?;
 
-    let mut algo_ctx = AlgoContext::new(
-        spec,
-        algo_config.individual_sample_size,
-        None,
-        explicit_init_value,
-    );
-
-    let mut evaled_individuals = FuturesUnordered::new();
-    let mut abort_signal_received = false;
-    let mut pushed_for_eval_count = 0;
+    let mut algo_ctx = Archipelago::new(spec.clone(), &algo_config, explicit_init_value);
 
-    let initial_num_individuals = if let Some(max_num_eval) = max_num_eval {
-        algo_config.num_concurrent.min(max_num_eval)
+    let mut eval_cache = if algo_config.eval_cache {
+        Some(EvalCache::new(
+            algo_config.eval_cache_file.as_deref(),
+            algo_config.individual_sample_size > 1,
+        )?)
     } else {
-        algo_config.num_concurrent
+        None
     };
 
-    for _ in 0..initial_num_individuals {
-        evaled_individuals.push(evaluate_individual(
-            algo_ctx.next_individual(),
-            &obj_func,
-            out_abort_signal_recv.clone(),
-            seed_mgr.next_seed(),
-        ));
-    }
+    let mut scheduler = algo_config.fidelity.as_ref().map(SuccessiveHalving::new);
+
+    let pruner = algo_config.pruning.as_ref().map(|pruning_config| {
+        Arc::new(Mutex::new(Pruner::new(
+            pruning_config,
+            algo_config.objective.maximize,
+        )))
+    });
 
-    pushed_for_eval_count += initial_num_individuals;
+    let objective_transformer = ObjectiveTransformer::new(&algo_config.objective);
+    let mut termination_expr =
+        TerminationExpr::new(&termination_criterion, algo_config.objective.maximize)?;
+    let mut termination_reason = None;
+    let max_num_eval = termination_criterion.max_num_obj_func_eval();
+
+    let mut evaled_batches = FuturesUnordered::new();
+    let mut termination_ctrl =
+        TerminationControl::new(abort_signal_sender, algo_config.drain_grace_period);
+    let mut pushed_for_eval_count = 0;
+
+    let batch_mode = algo_config.batch_size.is_some();
+    let stochastic = algo_config.individual_sample_size > 1;
+    let batch_size = algo_config.batch_size.unwrap_or(1);
+
+    let num_to_push = |pushed_for_eval_count: usize| {
+        max_num_eval.map_or(batch_size, |max_num_eval| {
+            batch_size.min(max_num_eval.saturating_sub(pushed_for_eval_count))
+        })
+    };
+
+    let mut num_concurrent = algo_config.num_concurrent;
+    let mut paused = false;
+    // values of the individuals being evaluated, by slot
+    let mut in_flight = BTreeMap::new();
+    let mut best_seen_key = None;
 
     let mut error_recording = None;
 
     loop {
+        while evaled_batches.len() < num_concurrent
+            && !paused
+            && !termination_ctrl.is_scheduling_stopped()
+        {
+            let num_jobs = num_to_push(pushed_for_eval_count);
+
+            if num_jobs == 0 {
+                break;
+            }
+
+            let eval_jobs: Vec<_> = (0..num_jobs)
+                .map(|_| {
+                    next_eval_job(
+                        &mut algo_ctx,
+                        &mut scheduler,
+                        &mut eval_cache,
+                        &mut seed_mgr,
+                        stochastic,
+                    )
+                })
+                .collect();
+
+            let slot = slot_mgr.acquire();
+
+            for eval_job in &eval_jobs {
+                let report = Report::IndividualEvalStarted {
+                    individual_id: eval_job.ind_ctx.id,
+                    individual: eval_job.ind_ctx.value.to_json(),
+                    seed: eval_job.seed,
+                    slot,
+                };
+
+                // the optimization never waits for the receiver of progress reports, and goes on
+                // without one
+                report_sender.unbounded_send(report).ok();
+            }
+
+            in_flight.insert(
+                slot,
+                eval_jobs
+                    .iter()
+                    .map(|eval_job| InFlightIndividual {
+                        individual_id: eval_job.ind_ctx.id,
+                        value: eval_job.ind_ctx.value.to_json(),
+                        slot,
+                    })
+                    .collect_vec(),
+            );
+
+            evaled_batches.push(evaluate_jobs(
+                eval_jobs,
+                slot,
+                slot_mgr.cpu_set(slot),
+                batch_mode,
+                &obj_func,
+                out_abort_signal_recv.clone(),
+                pruner.clone(),
+            ));
+
+            pushed_for_eval_count += num_jobs;
+        }
+
+        // while paused with nothing in flight, the run must not end for lack of evaluations
+        let idle = paused && evaled_batches.is_empty() && !termination_ctrl.is_scheduling_stopped();
+
+        let termination_timeout = async {
+            match termination_expr
+                .next_deadline()
+                .filter(|_| termination_reason.is_none())
+            {
+                Some(deadline) => {
+                    Delay::new(deadline.saturating_duration_since(Instant::now())).await
+                }
+                None => futures::future::pending().await,
+            }
+        };
+
+        let drain_timeout = async {
+            match termination_ctrl.drain_deadline() {
+                Some(deadline) => {
+                    Delay::new(deadline.saturating_duration_since(Instant::now())).await
+                }
+                None => futures::future::pending().await,
+            }
+        };
+
         tokio::select! {
-            evaled_individual = &mut evaled_individuals.try_next() => {
-                match evaled_individual {
+            evaled_batch = &mut evaled_batches.try_next(), if !idle => {
+                match evaled_batch {
 
                     Ok(None) => break,
-                    Ok(Some(evaled_individual)) => {
+                    Ok(Some((slot, evaled_batch))) => {
+                        slot_mgr.release(slot);
+                        in_flight.remove(&slot);
+
+                        for mut evaled_individual in evaled_batch {
+                            let is_full_fidelity = scheduler
+                                .as_ref()
+                                .zip(evaled_individual.rung_idx)
+                                .is_none_or(|(scheduler, rung_idx)| rung_idx == scheduler.top_rung_idx());
+
+                            // a value the objective transform is undefined for only rejects the evaluation, the reports keep the value
+                            if is_full_fidelity
+                                && evaled_individual.constraint_violation.is_none()
+                                && evaled_individual.obj_func_val.is_some_and(|obj_func_val| objective_transformer.transform(obj_func_val).is_err())
+                            {
+                                evaled_individual.rejection_reason = Some(RejectionReason::ObjectiveTransformUndefined);
+                            }
 
-                        let detailed_report_item = DetailedReportItem {
-                            individual_id: evaled_individual.ind_ctx.id,
-                            eval_time: evaled_individual.eval_time,
-                            meta_params_used: evaled_individual.ind_ctx.meta_params_used.clone(),
-                            input_val: evaled_individual.ind_ctx.value.to_json(),
-                            seed: evaled_individual.seed,
-                            obj_func_val: evaled_individual.obj_func_val.map(FiniteF64::get),
-                        };
+                            let detailed_report_item = DetailedReportItem {
+                                individual_id: evaled_individual.ind_ctx.id,
+                                eval_time: evaled_individual.eval_time,
+                                meta_params_used: evaled_individual.ind_ctx.meta_params_used.clone(),
+                                input_val: evaled_individual.ind_ctx.value.to_json(),
+                                seed: evaled_individual.seed,
+                                obj_func_val: evaled_individual.obj_func_val.map(FiniteF64::get),
+                                surrogate_prediction: evaled_individual.ind_ctx.surrogate_prediction,
+                                budget: evaled_individual.budget,
+                                full_fidelity: is_full_fidelity,
+                                pruned: evaled_individual.pruned,
+                                constraint_violation: evaled_individual.constraint_violation.map(FiniteF64::get),
+                                rejection_reason: evaled_individual.rejection_reason,
+                                slot: evaled_individual.slot,
+                                cpu_set: evaled_individual.slot.and_then(|slot| slot_mgr.cpu_set(slot)),
+                            };
+
+                            detailed_report_sender.send(detailed_report_item).await.map_err(|_err| Error::ClientHungUp)?;
+
+                            if let Some(eval_cache) = &mut eval_cache {
+                                // results obtained after an abort may stem from killed evaluations, and whether resource
+                                // limits are exceeded may depend on the load of the host
+                                if !evaled_individual.from_cache && !evaled_individual.pruned && evaled_individual.rejection_reason.is_none() && !termination_ctrl.is_aborted() {
+                                    eval_cache.insert(
+                                        &evaled_individual.ind_ctx.value,
+                                        evaled_individual.seed,
+                                        evaled_individual.budget,
+                                        ObjFuncResult {
+                                            obj_func_val: evaled_individual.obj_func_val.map(FiniteF64::get),
+                                            constraint_violation: evaled_individual.constraint_violation.map(FiniteF64::get),
+                                            rejection_reason: None,
+                                            num_retries: 0,
+                                        },
+                                    )?;
+                                }
+                            }
 
-                        detailed_report_sender.send(detailed_report_item).await.map_err(|_err| Error::ClientHungUp)?;
+                            if evaled_individual.pruned {
+                                count_pruned += 1;
+                            } else if evaled_individual.rejection_reason == Some(RejectionReason::ExitCode) {
+                                count_rejected_by_exit_code += 1;
+                            } else if matches!(evaled_individual.rejection_reason, Some(RejectionReason::Timeout | RejectionReason::ObjectiveTransformUndefined)) {
+                                // timeouts count as rejected, as they did before they came with a reason
+                                count_rejected += 1;
+                            } else if evaled_individual.rejection_reason.is_some() {
+                                count_limit_exceeded += 1;
+                            } else if evaled_individual.constraint_violation.is_some() {
+                                count_infeasible += 1;
+                            } else if evaled_individual.obj_func_val.is_some() {
+                                count_accepted += 1;
+                            } else {
+                                count_rejected += 1;
+                            }
 
-                        if evaled_individual.obj_func_val.is_some() {
-                            count_accepted += 1;
-                        } else {
-                            count_rejected += 1;
-                        }
-                        algo_ctx.process_individual_eval(evaled_individual.ind_ctx, evaled_individual.obj_func_val);
+                            count_retries += evaled_individual.num_retries;
 
+                            report_sender.unbounded_send(make_eval_report(&evaled_individual)).ok();
 
-                        if let (Some(target_obj_func_val), Some(best_seen_final)) =
-                        (target_obj_func_val, algo_ctx.best_seen_final()) {
-                            if best_seen_final.0.get() <= target_obj_func_val {
-                                break;
+                            let constraint_violation = evaled_individual.constraint_violation;
+                            let mut ind_ctx = evaled_individual.ind_ctx;
+
+                            // infeasible individuals are never promoted to higher budgets
+                            let feasible_obj_func_val = if constraint_violation.is_some() || evaled_individual.rejection_reason.is_some() {
+                                None
+                            } else {
+                                evaled_individual.obj_func_val
+                            };
+
+                            // reports show values as returned by the objective function, the algorithm sees transformed ones
+                            if let (Some(obj_func_val), true) = (feasible_obj_func_val, is_full_fidelity) {
+                                ind_ctx.raw_obj_func_vals.push(obj_func_val.get());
                             }
-                        }
 
-                        let (max_num_eval_pushed, max_num_eval_completed) = if let Some(max_num_eval) = max_num_eval {
-                            (pushed_for_eval_count >= max_num_eval, count_accepted + count_rejected >= max_num_eval)
-                        } else {
-                            (false, false)
-                        };
-
-                        if max_num_eval_completed {
-                            break;
-                        } else if !max_num_eval_pushed && !abort_signal_received {
-                            let new_individual = algo_ctx.next_individual();
-                            let eval_future = evaluate_individual(new_individual, &obj_func, out_abort_signal_recv.clone(),
-                                seed_mgr.next_seed());
-                            evaled_individuals.push(eval_future);
-                            pushed_for_eval_count += 1;
+                            // lower budget results are only compared with each other within their rung, orienting them suffices
+                            let transformed_obj_func_val = if is_full_fidelity {
+                                feasible_obj_func_val
+                                    .map(|obj_func_val| objective_transformer.transform(obj_func_val))
+                                    .transpose()?
+                            } else {
+                                feasible_obj_func_val
+                                    .map(|obj_func_val| FiniteF64::new(objective_transformer.orient(obj_func_val.get())).unwrap())
+                            };
+
+                            let full_fidelity_result = match (&mut scheduler, evaled_individual.rung_idx) {
+                                (Some(scheduler), Some(rung_idx)) => scheduler.process_result(
+                                    ind_ctx,
+                                    rung_idx,
+                                    transformed_obj_func_val,
+                                ),
+                                _ => Some((ind_ctx, transformed_obj_func_val)),
+                            };
+
+                            if let Some((ind_ctx, obj_func_val)) = full_fidelity_result {
+                                match constraint_violation {
+                                    Some(constraint_violation) => algo_ctx.process_infeasible_eval(ind_ctx, constraint_violation),
+                                    None => algo_ctx.process_individual_eval(ind_ctx, obj_func_val),
+                                }
+                            }
+
+                            if let Some((obj_func_val, best_seen)) = raw_best_seen(&algo_ctx) {
+                                if best_seen_key != Some((best_seen.id, obj_func_val)) {
+                                    best_seen_key = Some((best_seen.id, obj_func_val));
+                                    let report = Report::NewBestSeen {
+                                        individual_id: best_seen.id,
+                                        obj_func_val,
+                                        individual: best_seen.value.to_json(),
+                                    };
+                                    report_sender.unbounded_send(report).ok();
+                                }
+                            }
+
+                            let best_seen_obj_func_val = oriented_best_seen_obj_func_val(&algo_ctx, &objective_transformer);
+                            termination_expr.record_eval(best_seen_obj_func_val);
+
+                            if termination_reason.is_none() {
+                                let num_eval = count_accepted + count_rejected + count_pruned + count_infeasible + count_limit_exceeded + count_rejected_by_exit_code;
+
+                                if let Some(criterion) = termination_expr.fired(num_eval, best_seen_obj_func_val) {
+                                    info!("Termination criterion fired: {}", criterion);
+                                    termination_reason = Some(criterion.clone());
+                                    termination_ctrl.request_termination();
+                                }
+                            }
                         }
                     }
                     Err(error) => {
-                        if !abort_signal_received {
-                            abort_signal_received = true;
-                            abort_signal_sender.broadcast(()).await.unwrap();
+                        if !termination_ctrl.is_aborted() {
+                            termination_ctrl.abort();
                             error_recording = Some(error);
                         }
                     }
                 }
             }
-            _ = &mut in_abort_signal_recv => {
-                if !abort_signal_received {
-                    abort_signal_received = true;
-                    abort_signal_sender.broadcast(()).await.unwrap();
+            _ = termination_timeout => {
+                let best_seen_obj_func_val = oriented_best_seen_obj_func_val(&algo_ctx, &objective_transformer);
+                let num_eval = count_accepted + count_rejected + count_pruned + count_infeasible + count_limit_exceeded + count_rejected_by_exit_code;
+
+                if let Some(criterion) = termination_expr.fired(num_eval, best_seen_obj_func_val) {
+                    info!("Termination criterion fired: {}", criterion);
+                    termination_reason = Some(criterion.clone());
+                    termination_ctrl.request_termination();
+                }
+            }
+            _ = drain_timeout => {
+                info!("Drain grace period elapsed, aborting remaining evaluations");
+                termination_ctrl.abort();
+            }
+            Some(control_request) = control_request_recv.next() => {
+                let response = match control_request.command {
+                    Command::Terminate => {
+                        termination_reason.get_or_insert(TerminationCriterion::Signal);
+                        termination_ctrl.request_termination();
+                        Response::Ok
+                    }
+                    Command::Pause => {
+                        info!("Pausing scheduling of evaluations");
+                        paused = true;
+                        Response::Ok
+                    }
+                    Command::Resume => {
+                        info!("Resuming scheduling of evaluations");
+                        paused = false;
+                        Response::Ok
+                    }
+                    Command::SetNumConcurrent { num_concurrent: 0 } => Response::Error {
+                        message: "number of concurrent evaluations must be positive, pause instead".to_string(),
+                    },
+                    Command::SetNumConcurrent { num_concurrent: new_num_concurrent } => {
+                        if slot_mgr.ensure_num_slots(new_num_concurrent) {
+                            info!("Number of concurrent evaluations set to {}", new_num_concurrent);
+                            num_concurrent = new_num_concurrent;
+                            Response::Ok
+                        } else {
+                            Response::Error {
+                                message: "with CPU pinning, the number of concurrent evaluations cannot be raised".to_string(),
+                            }
+                        }
+                    }
+                    Command::Inject { value } => match value_util::from_json_value(&value, &spec) {
+                        Ok(value) => {
+                            algo_ctx.inject(value);
+                            Response::Ok
+                        }
+                        Err(err) => Response::Error { message: err.to_string() },
+                    },
+                    Command::Status => {
+                        let best_seen = raw_best_seen(&algo_ctx);
+
+                        Response::Status(Status {
+                            paused,
+                            num_concurrent,
+                            best_seen_obj_func_val: best_seen.map(|best_seen| best_seen.0),
+                            best_seen: best_seen.map(|best_seen| best_seen.1.value.to_json()),
+                            num_obj_func_eval_completed: count_accepted,
+                            num_obj_func_eval_rejected: count_rejected,
+                            num_obj_func_eval_pruned: count_pruned,
+                            num_obj_func_eval_infeasible: count_infeasible,
+                            num_obj_func_eval_limit_exceeded: count_limit_exceeded,
+                            num_obj_func_eval_rejected_by_exit_code: count_rejected_by_exit_code,
+                            num_obj_func_eval_retries: count_retries,
+                            in_flight: in_flight.values().flatten().cloned().collect(),
+                        })
+                    }
+                };
+
+                if let Some(reply) = control_request.reply {
+                    reply.send(response).ok();
                 }
             }
         }
     }
 
+    let report = Report::Terminated {
+        termination_reason: termination_reason.as_ref().map(ToString::to_string),
+    };
+    report_sender.unbounded_send(report).ok();
+
     if let Some(error) = error_recording {
         return Err(error);
     }
 
     info!("Processing completed");
 
-    match algo_ctx.best_seen_final() {
+    let (num_eval_cache_hits, num_eval_cache_misses) = eval_cache
+        .map(|eval_cache| (eval_cache.num_hits(), eval_cache.num_misses()))
+        .unwrap_or_default();
+
+    match raw_best_seen(&algo_ctx) {
         Some(best_seen) => Ok(FinalReport::new(
-            best_seen.0.get(),
-            best_seen.1.to_json(),
+            best_seen.0,
+            best_seen.1.value.to_json(),
             count_accepted,
             count_rejected,
+            count_pruned,
+            count_infeasible,
+            count_limit_exceeded,
+            count_rejected_by_exit_code,
+            count_retries,
+            num_eval_cache_hits,
+            num_eval_cache_misses,
             start_ts.elapsed(),
+            termination_reason,
         )),
         None => Err(Error::NoIndividuals),
     }
 }
 
+// with the objective function value as returned by the objective function
+fn raw_best_seen(algo_ctx: &Archipelago) -> Option<(f64, &IndContext)> {
+    algo_ctx.best_seen_final().map(|(obj_func_val, ind_ctx)| {
+        (
+            ind_ctx
+                .raw_obj_func_val()
+                .unwrap_or_else(|| obj_func_val.get()),
+            ind_ctx,
+        )
+    })
+}
+
+fn make_eval_report(evaled_individual: &EvaluatedIndividual) -> Report {
+    let individual_id = evaled_individual.ind_ctx.id;
+    let individual = evaled_individual.ind_ctx.value.to_json();
+
+    match (
+        evaled_individual.pruned,
+        evaled_individual.rejection_reason,
+        evaled_individual.obj_func_val,
+    ) {
+        (true, _, _) => Report::IndividualEvalPruned {
+            individual_id,
+            individual,
+        },
+        (false, Some(RejectionReason::Timeout), _) => Report::IndividualEvalTimedOut {
+            individual_id,
+            individual,
+        },
+        (false, None, Some(obj_func_val)) => Report::IndividualEvalCompleted {
+            individual_id,
+            obj_func_val: obj_func_val.get(),
+            constraint_violation: evaled_individual.constraint_violation.map(FiniteF64::get),
+            individual,
+        },
+        (false, rejection_reason, _) => Report::IndividualEvalRejected {
+            individual_id,
+            rejection_reason,
+            individual,
+        },
+    }
+}
+
+fn oriented_best_seen_obj_func_val(
+    algo_ctx: &Archipelago,
+    objective_transformer: &ObjectiveTransformer,
+) -> Option<f64> {
+    algo_ctx
+        .best_seen_final()
+        .and_then(|best_seen| best_seen.1.raw_obj_func_val())
+        .map(|obj_func_val| objective_transformer.orient(obj_func_val))
+}
+
+// On termination, in-flight evaluations are either aborted right away or, if a drain grace
+// period is configured, given that much time to complete. A repeated request aborts them.
+struct TerminationControl {
+    abort_signal_sender: async_broadcast::Sender<()>,
+    drain_grace_period: Option<Duration>,
+    drain_deadline: Option<Instant>,
+    aborted: bool,
+}
+
+impl TerminationControl {
+    fn new(
+        abort_signal_sender: async_broadcast::Sender<()>,
+        drain_grace_period: Option<Duration>,
+    ) -> Self {
+        Self {
+            abort_signal_sender,
+            drain_grace_period,
+            drain_deadline: None,
+            aborted: false,
+        }
+    }
+
+    fn request_termination(&mut self) {
+        match (self.drain_grace_period, self.drain_deadline) {
+            (Some(drain_grace_period), None) if !self.aborted => {
+                info!("Draining in-flight evaluations");
+                self.drain_deadline = Some(Instant::now() + drain_grace_period);
+            }
+            _ => self.abort(),
+        }
+    }
+
+    fn abort(&mut self) {
+        if !self.aborted {
+            self.aborted = true;
+            self.abort_signal_sender.try_broadcast(()).unwrap();
+        }
+    }
+
+    fn drain_deadline(&self) -> Option<Instant> {
+        self.drain_deadline.filter(|_| !self.aborted)
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    fn is_scheduling_stopped(&self) -> bool {
+        self.aborted || self.drain_deadline.is_some()
+    }
+}
+
 struct EvaluatedIndividual {
     obj_func_val: Option<FiniteF64>,
     ind_ctx: IndContext,
     eval_time: Duration,
     seed: u64,
+    rung_idx: Option<usize>,
+    budget: Option<f64>,
+    from_cache: bool,
+    pruned: bool,
+    constraint_violation: Option<FiniteF64>,
+    rejection_reason: Option<RejectionReason>,
+    num_retries: usize,
+    // none for results taken from the cache
+    slot: Option<usize>,
+}
+
+struct EvalJob {
+    ind_ctx: IndContext,
+    seed: u64,
+    rung_idx: Option<usize>,
+    budget: Option<f64>,
+    sample_index: Option<usize>,
+    cached_result: Option<ObjFuncResult>,
+}
+
+fn next_eval_job(
+    algo_ctx: &mut Archipelago,
+    scheduler: &mut Option<SuccessiveHalving>,
+    eval_cache: &mut Option<EvalCache>,
+    seed_mgr: &mut SeedManager,
+    stochastic: bool,
+) -> EvalJob {
+    let (ind_ctx, rung_idx) = match scheduler {
+        Some(scheduler) => match scheduler.next_promotion() {
+            Some((ind_ctx, rung_idx)) => (ind_ctx, Some(rung_idx)),
+            None => {
+                let ind_ctx = algo_ctx.next_individual();
+
+                // individuals selected for re-evaluation have already passed all lower budgets
+                let rung_idx = if ind_ctx.is_pending_reeval() {
+                    scheduler.top_rung_idx()
+                } else {
+                    0
+                };
+
+                (ind_ctx, Some(rung_idx))
+            }
+        },
+        None => (algo_ctx.next_individual(), None),
+    };
+
+    let budget = scheduler
+        .as_ref()
+        .zip(rung_idx)
+        .map(|(scheduler, rung_idx)| scheduler.budget(rung_idx));
+
+    let seed = seed_mgr.next_seed();
+    let sample_index = stochastic.then(|| ind_ctx.sample_index());
+
+    let cached_result = eval_cache
+        .as_mut()
+        .and_then(|eval_cache| eval_cache.lookup(&ind_ctx.value, seed, budget));
+
+    if cached_result.is_some() {
+        info!("Individual {}: result taken from cache", ind_ctx.id);
+    }
+
+    EvalJob {
+        ind_ctx,
+        seed,
+        rung_idx,
+        budget,
+        sample_index,
+        cached_result,
+    }
+}
+
+// all jobs passed in run in the same slot, which is returned along with the results
+async fn evaluate_jobs<F: AsyncObjectiveFunction>(
+    eval_jobs: Vec<EvalJob>,
+    slot: usize,
+    cpu_set: Option<Vec<usize>>,
+    batch_mode: bool,
+    obj_func: &F,
+    abort_signal_recv: async_broadcast::Receiver<()>,
+    pruner: Option<Arc<Mutex<Pruner>>>,
+) -> Result<(usize, Vec<EvaluatedIndividual>), Error> {
+    let evaled_individuals = if batch_mode {
+        evaluate_batch(eval_jobs, slot, cpu_set, obj_func, abort_signal_recv).await?
+    } else {
+        let eval_futures = eval_jobs.into_iter().map(|eval_job| {
+            evaluate_individual(
+                eval_job,
+                slot,
+                cpu_set.clone(),
+                obj_func,
+                abort_signal_recv.clone(),
+                pruner.clone(),
+            )
+        });
+
+        futures::future::try_join_all(eval_futures).await?
+    };
+
+    Ok((slot, evaled_individuals))
+}
+
+fn make_eval_context(eval_job: &EvalJob, slot: usize, cpu_set: Option<Vec<usize>>) -> EvalContext {
+    EvalContext {
+        seed: eval_job.seed,
+        budget: eval_job.budget,
+        individual_id: eval_job.ind_ctx.id,
+        sample_index: eval_job.sample_index,
+        slot,
+        cpu_set,
+    }
 }
 
 async fn evaluate_individual<F: AsyncObjectiveFunction>(
-    individual: IndContext,
+    mut eval_job: EvalJob,
+    slot: usize,
+    cpu_set: Option<Vec<usize>>,
     obj_func: &F,
     abort_signal_recv: async_broadcast::Receiver<()>,
-    seed: u64,
+    pruner: Option<Arc<Mutex<Pruner>>>,
 ) -> Result<EvaluatedIndividual, Error> {
     let start_time = Instant::now();
+    let intermediate_reporter = IntermediateReporter::new(pruner, eval_job.ind_ctx.id);
+
+    let (eval_result, from_cache) = match eval_job.cached_result.take() {
+        Some(cached_result) => (cached_result, true),
+        None => {
+            let eval_result = obj_func
+                .evaluate(
+                    eval_job.ind_ctx.value.to_json(),
+                    abort_signal_recv,
+                    &make_eval_context(&eval_job, slot, cpu_set),
+                    &intermediate_reporter,
+                )
+                .await?;
+
+            (eval_result, false)
+        }
+    };
 
-    let eval_result = obj_func
-        .evaluate(
-            individual.value.to_json(),
-            abort_signal_recv,
-            seed,
-            individual.id,
-        )
-        .await?;
+    make_evaluated_individual(
+        eval_job,
+        eval_result,
+        start_time.elapsed(),
+        (!from_cache).then_some(slot),
+        intermediate_reporter.is_pruned(),
+    )
+}
+
+async fn evaluate_batch<F: AsyncObjectiveFunction>(
+    eval_jobs: Vec<EvalJob>,
+    slot: usize,
+    cpu_set: Option<Vec<usize>>,
+    obj_func: &F,
+    abort_signal_recv: async_broadcast::Receiver<()>,
+) -> Result<Vec<EvaluatedIndividual>, Error> {
+    let start_time = Instant::now();
+
+    let (cached_jobs, uncached_jobs): (Vec<_>, Vec<_>) = eval_jobs
+        .into_iter()
+        .partition(|eval_job| eval_job.cached_result.is_some());
+
+    let batch_items = uncached_jobs
+        .iter()
+        .map(|eval_job| BatchItem {
+            value: eval_job.ind_ctx.value.to_json(),
+            eval_ctx: make_eval_context(eval_job, slot, cpu_set.clone()),
+        })
+        .collect_vec();
+
+    let eval_results = if batch_items.is_empty() {
+        Vec::new()
+    } else {
+        obj_func
+            .evaluate_batch(batch_items, abort_signal_recv)
+            .await?
+    };
 
     let eval_time = start_time.elapsed();
 
+    cached_jobs
+        .into_iter()
+        .map(|mut eval_job| {
+            let cached_result = eval_job.cached_result.take().unwrap();
+            make_evaluated_individual(eval_job, cached_result, Duration::ZERO, None, false)
+        })
+        .chain(
+            uncached_jobs
+                .into_iter()
+                .zip(eval_results)
+                .map(|(eval_job, eval_result)| {
+                    make_evaluated_individual(eval_job, eval_result, eval_time, Some(slot), false)
+                }),
+        )
+        .collect()
+}
+
+fn make_evaluated_individual(
+    eval_job: EvalJob,
+    eval_result: ObjFuncResult,
+    eval_time: Duration,
+    slot: Option<usize>,
+    pruned: bool,
+) -> Result<EvaluatedIndividual, Error> {
     let finitified_result = eval_result
+        .obj_func_val
         .map(FiniteF64::new)
         .transpose()
         .map_err(|_| Error::ObjFuncValMustBeFinite)?;
 
+    let constraint_violation = eval_result
+        .constraint_violation
+        .map(FiniteF64::new)
+        .transpose()
+        .map_err(|_| Error::ConstraintViolationMustBeFinite)?
+        .filter(|constraint_violation| constraint_violation.get() > 0.0);
+
     Ok(EvaluatedIndividual {
         obj_func_val: finitified_result,
-        ind_ctx: individual,
+        ind_ctx: eval_job.ind_ctx,
         eval_time,
-        seed,
+        seed: eval_job.seed,
+        rung_idx: eval_job.rung_idx,
+        budget: eval_job.budget,
+        from_cache: slot.is_none(),
+        pruned,
+        constraint_violation,
+        rejection_reason: eval_result.rejection_reason,
+        num_retries: eval_result.num_retries,
+        slot,
     })
 }
 
+struct SlotManager {
+    free_slots: BTreeSet<usize>,
+    num_slots: usize,
+    cpu_sets: Option<Vec<Vec<usize>>>,
+}
+
+impl SlotManager {
+    fn new(num_slots: usize, cpu_sets: Option<Vec<Vec<usize>>>) -> Self {
+        Self {
+            free_slots: (0..num_slots).collect(),
+            num_slots,
+            cpu_sets,
+        }
+    }
+
+    // slots are only ever added, and not beyond the CPU sets set up for pinning
+    fn ensure_num_slots(&mut self, num_slots: usize) -> bool {
+        if let Some(cpu_sets) = &self.cpu_sets {
+            if num_slots > cpu_sets.len() {
+                return false;
+            }
+        }
+
+        self.free_slots.extend(self.num_slots..num_slots);
+        self.num_slots = self.num_slots.max(num_slots);
+        true
+    }
+
+    fn acquire(&mut self) -> usize {
+        self.free_slots.pop_first().unwrap()
+    }
+
+    fn release(&mut self, slot: usize) {
+        self.free_slots.insert(slot);
+    }
+
+    fn cpu_set(&self, slot: usize) -> Option<Vec<usize>> {
+        self.cpu_sets
+            .as_ref()
+            .map(|cpu_sets| cpu_sets[slot].clone())
+    }
+}
+
 struct SeedManager {
     next_seed: u64,
 }
@@ -0,0 +1,616 @@
+use crate::distributed::{
+    self, CoordinatorMessage, Endpoint, Job, Listener, Reader, StdListener, WorkerMessage, Writer,
+};
+use crate::error::Error;
+use crate::meta::{
+    AlgoConfig, AsyncObjectiveFunction, EvalContext, ObjFuncResult, RejectionReason,
+};
+use crate::pruning::IntermediateReporter;
+use async_trait::async_trait;
+use log::{info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::{mpsc, oneshot};
+
+type Reply = oneshot::Sender<Result<ObjFuncResult, String>>;
+
+enum Event {
+    Submit {
+        job: Job,
+        reply: Reply,
+        started: oneshot::Sender<()>,
+    },
+    Cancel {
+        id: u64,
+    },
+    WorkerConnected {
+        worker_id: usize,
+        capacity: usize,
+        sender: mpsc::UnboundedSender<CoordinatorMessage>,
+    },
+    WorkerMessage {
+        worker_id: usize,
+        message: WorkerMessage,
+    },
+    WorkerDisconnected {
+        worker_id: usize,
+    },
+}
+
+// Objective function evaluated by remote workers. Jobs are assigned to workers with free
+// capacity, and those of workers that disconnect or stop sending heartbeats are reassigned.
+pub struct Coordinator {
+    endpoint: Endpoint,
+    next_job_id: AtomicU64,
+    kill_obj_func_after: Option<Duration>,
+    event_sender: mpsc::UnboundedSender<Event>,
+    // serving requires the runtime of the launch, so it starts with the first evaluation
+    startup: Mutex<Option<(StdListener, Dispatcher, mpsc::UnboundedReceiver<Event>)>>,
+    socket_path: Option<PathBuf>,
+}
+
+impl Coordinator {
+    pub fn bind(
+        endpoint: &Endpoint,
+        heartbeat_timeout: Duration,
+        kill_obj_func_after: Option<Duration>,
+    ) -> Result<Self, Error> {
+        if let Endpoint::Unix(path) = endpoint {
+            distributed::remove_stale_socket(path);
+        }
+
+        let listener =
+            StdListener::bind(endpoint).map_err(|err| Error::UnableToListenForWorkers {
+                endpoint: endpoint.to_string(),
+                source: err,
+            })?;
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+
+        Ok(Self {
+            endpoint: listener.local_endpoint()?,
+            next_job_id: AtomicU64::new(0),
+            kill_obj_func_after,
+            event_sender,
+            startup: Mutex::new(Some((
+                listener,
+                Dispatcher::new(heartbeat_timeout),
+                event_receiver,
+            ))),
+            socket_path: match endpoint {
+                Endpoint::Unix(path) => Some(path.clone()),
+                Endpoint::Tcp(_) => None,
+            },
+        })
+    }
+
+    // with the actual port if port 0 was requested
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    fn ensure_started(&self) -> Result<(), Error> {
+        if let Some((listener, dispatcher, event_receiver)) = self.startup.lock().unwrap().take() {
+            let listener = listener.into_async()?;
+            tokio::spawn(accept_workers(listener, self.event_sender.clone()));
+            tokio::spawn(dispatcher.run(event_receiver));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Coordinator {
+    fn drop(&mut self) {
+        if let Some(socket_path) = &self.socket_path {
+            std::fs::remove_file(socket_path).ok();
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncObjectiveFunction for Coordinator {
+    async fn evaluate(
+        &self,
+        value: serde_json::Value,
+        mut abort_sig_rx: async_broadcast::Receiver<()>,
+        eval_ctx: &EvalContext,
+        _intermediate_reporter: &IntermediateReporter,
+    ) -> Result<ObjFuncResult, Error> {
+        self.ensure_started()?;
+
+        let id = self.next_job_id.fetch_add(1, Ordering::Relaxed);
+        let job = Job {
+            id,
+            individual_id: eval_ctx.individual_id,
+            params: value,
+            seed: eval_ctx.seed,
+            budget: eval_ctx.budget,
+            sample_index: eval_ctx.sample_index,
+        };
+
+        let (reply, reply_rx) = oneshot::channel();
+        let (started, started_rx) = oneshot::channel();
+        self.event_sender
+            .send(Event::Submit {
+                job,
+                reply,
+                started,
+            })
+            .map_err(|_| Error::CoordinatorStopped)?;
+
+        // counted from the first assignment to a worker, as time spent in the queue is no
+        // fault of the evaluation
+        let timeout = async {
+            match (started_rx.await, self.kill_obj_func_after) {
+                (Ok(()), Some(kill_obj_func_after)) => {
+                    tokio::time::sleep(kill_obj_func_after).await
+                }
+                _ => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            _ = abort_sig_rx.recv() => {
+                self.event_sender.send(Event::Cancel { id }).ok();
+                Ok(ObjFuncResult::rejected())
+            }
+            _ = timeout => {
+                self.event_sender.send(Event::Cancel { id }).ok();
+                Ok(ObjFuncResult::rejected_with_reason(RejectionReason::Timeout))
+            }
+            reply = reply_rx => match reply {
+                Ok(Ok(result)) => Ok(result),
+                Ok(Err(message)) => Err(Error::WorkerEvaluationFailed(message)),
+                Err(_) => Err(Error::CoordinatorStopped),
+            }
+        }
+    }
+
+    // workers have no way to send intermediate reports
+    fn validate(&self, algo_config: &AlgoConfig) -> Result<(), Error> {
+        if algo_config.pruning.is_some() {
+            return Err(Error::PruningInDistributedMode);
+        }
+
+        Ok(())
+    }
+}
+
+async fn accept_workers(listener: Listener, event_sender: mpsc::UnboundedSender<Event>) {
+    let mut next_worker_id = 0;
+
+    loop {
+        match listener.accept().await {
+            Ok((reader, writer)) => {
+                let worker_id = next_worker_id;
+                next_worker_id += 1;
+                tokio::spawn(serve_worker(
+                    worker_id,
+                    reader,
+                    writer,
+                    event_sender.clone(),
+                ));
+            }
+            Err(err) => warn!("Failed to accept worker connection: {}", err),
+        }
+    }
+}
+
+async fn serve_worker(
+    worker_id: usize,
+    reader: Reader,
+    mut writer: Writer,
+    event_sender: mpsc::UnboundedSender<Event>,
+) {
+    let mut lines = reader.lines();
+
+    let capacity = match lines.next_line().await {
+        Ok(Some(line)) => match distributed::parse_message(&line) {
+            Ok(WorkerMessage::Hello { capacity }) => capacity,
+            _ => {
+                warn!("Worker {}: expected hello, got: {}", worker_id, line);
+                return;
+            }
+        },
+        _ => return,
+    };
+
+    let (sender, mut message_receiver) = mpsc::unbounded_channel();
+
+    // the connection is shut down once the dispatcher drops the sender
+    tokio::spawn(async move {
+        while let Some(message) = message_receiver.recv().await {
+            if distributed::write_message(&mut writer, &message)
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    event_sender
+        .send(Event::WorkerConnected {
+            worker_id,
+            capacity,
+            sender,
+        })
+        .ok();
+
+    loop {
+        let message = match lines.next_line().await {
+            Ok(Some(line)) => match distributed::parse_message(&line) {
+                Ok(message) => message,
+                Err(err) => {
+                    warn!("Worker {}: {}", worker_id, err);
+                    break;
+                }
+            },
+            _ => break,
+        };
+
+        if event_sender
+            .send(Event::WorkerMessage { worker_id, message })
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    event_sender
+        .send(Event::WorkerDisconnected { worker_id })
+        .ok();
+}
+
+struct PendingJob {
+    job: Job,
+    reply: Reply,
+    started: Option<oneshot::Sender<()>>,
+    worker_id: Option<usize>,
+}
+
+struct WorkerState {
+    sender: mpsc::UnboundedSender<CoordinatorMessage>,
+    capacity: usize,
+    // including cancelled jobs, until the worker reports they have ended
+    job_ids: HashSet<u64>,
+    last_seen: Instant,
+}
+
+struct Dispatcher {
+    heartbeat_timeout: Duration,
+    queue: VecDeque<u64>,
+    jobs: HashMap<u64, PendingJob>,
+    workers: BTreeMap<usize, WorkerState>,
+}
+
+impl Dispatcher {
+    fn new(heartbeat_timeout: Duration) -> Self {
+        Self {
+            heartbeat_timeout,
+            queue: VecDeque::new(),
+            jobs: HashMap::new(),
+            workers: BTreeMap::new(),
+        }
+    }
+
+    async fn run(mut self, mut event_receiver: mpsc::UnboundedReceiver<Event>) {
+        let mut heartbeat_check = tokio::time::interval(self.heartbeat_timeout / 2);
+
+        loop {
+            tokio::select! {
+                event = event_receiver.recv() => match event {
+                    Some(event) => self.handle(event),
+                    None => return,
+                },
+                _ = heartbeat_check.tick() => self.remove_silent_workers(),
+            }
+
+            self.dispatch();
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Submit {
+                job,
+                reply,
+                started,
+            } => {
+                self.queue.push_back(job.id);
+                self.jobs.insert(
+                    job.id,
+                    PendingJob {
+                        job,
+                        reply,
+                        started: Some(started),
+                        worker_id: None,
+                    },
+                );
+            }
+            Event::Cancel { id } => {
+                if let Some(pending_job) = self.jobs.remove(&id) {
+                    match pending_job
+                        .worker_id
+                        .and_then(|worker_id| self.workers.get_mut(&worker_id))
+                    {
+                        Some(worker) => {
+                            worker.sender.send(CoordinatorMessage::Cancel { id }).ok();
+                        }
+                        None => self.queue.retain(|queued_id| *queued_id != id),
+                    }
+                }
+            }
+            Event::WorkerConnected {
+                worker_id,
+                capacity,
+                sender,
+            } => {
+                info!("Worker {} connected, capacity: {}", worker_id, capacity);
+                self.workers.insert(
+                    worker_id,
+                    WorkerState {
+                        sender,
+                        capacity,
+                        job_ids: HashSet::new(),
+                        last_seen: Instant::now(),
+                    },
+                );
+            }
+            Event::WorkerMessage { worker_id, message } => {
+                if let Some(worker) = self.workers.get_mut(&worker_id) {
+                    worker.last_seen = Instant::now();
+                }
+
+                match message {
+                    WorkerMessage::Result { id, result } => {
+                        self.complete(worker_id, id, Ok(result.into()))
+                    }
+                    WorkerMessage::Failed { id, message } => {
+                        self.complete(worker_id, id, Err(message))
+                    }
+                    WorkerMessage::Hello { .. } | WorkerMessage::Heartbeat => (),
+                }
+            }
+            Event::WorkerDisconnected { worker_id } => self.remove_worker(worker_id),
+        }
+    }
+
+    // results of jobs that were cancelled or reassigned in the meantime are dropped
+    fn complete(&mut self, worker_id: usize, id: u64, result: Result<ObjFuncResult, String>) {
+        let assigned = self
+            .workers
+            .get_mut(&worker_id)
+            .is_some_and(|worker| worker.job_ids.remove(&id));
+
+        if assigned {
+            if let Some(pending_job) = self.jobs.remove(&id) {
+                pending_job.reply.send(result).ok();
+            }
+        }
+    }
+
+    fn remove_silent_workers(&mut self) {
+        let silent_worker_ids: Vec<_> = self
+            .workers
+            .iter()
+            .filter(|(_, worker)| worker.last_seen.elapsed() > self.heartbeat_timeout)
+            .map(|(worker_id, _)| *worker_id)
+            .collect();
+
+        for worker_id in silent_worker_ids {
+            warn!("Worker {}: no heartbeat received", worker_id);
+            self.remove_worker(worker_id);
+        }
+    }
+
+    fn remove_worker(&mut self, worker_id: usize) {
+        if let Some(worker) = self.workers.remove(&worker_id) {
+            info!(
+                "Worker {} lost, reassigning {} jobs",
+                worker_id,
+                worker.job_ids.len()
+            );
+
+            // reassigned before all other queued jobs, in the order they were submitted
+            let mut job_ids: Vec<_> = worker.job_ids.into_iter().collect();
+            job_ids.sort_unstable();
+
+            for id in job_ids.into_iter().rev() {
+                if let Some(pending_job) = self.jobs.get_mut(&id) {
+                    pending_job.worker_id = None;
+                    self.queue.push_front(id);
+                }
+            }
+        }
+    }
+
+    fn dispatch(&mut self) {
+        while let Some(&id) = self.queue.front() {
+            let worker_id = match self
+                .workers
+                .iter()
+                .filter(|(_, worker)| worker.job_ids.len() < worker.capacity)
+                .min_by_key(|(_, worker)| worker.job_ids.len())
+            {
+                Some((worker_id, _)) => *worker_id,
+                None => return,
+            };
+
+            self.queue.pop_front();
+
+            let pending_job = match self.jobs.get_mut(&id) {
+                Some(pending_job) => pending_job,
+                None => continue,
+            };
+
+            let worker = self.workers.get_mut(&worker_id).unwrap();
+
+            if worker
+                .sender
+                .send(CoordinatorMessage::Job(pending_job.job.clone()))
+                .is_ok()
+            {
+                pending_job.worker_id = Some(worker_id);
+                worker.job_ids.insert(id);
+
+                if let Some(started) = pending_job.started.take() {
+                    started.send(()).ok();
+                }
+            } else {
+                self.queue.push_front(id);
+                self.remove_worker(worker_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_job(id: u64) -> Job {
+        Job {
+            id,
+            individual_id: id as usize,
+            params: serde_json::Value::Null,
+            seed: id,
+            budget: None,
+            sample_index: None,
+        }
+    }
+
+    fn connect_worker(
+        sut: &mut Dispatcher,
+        worker_id: usize,
+        capacity: usize,
+    ) -> mpsc::UnboundedReceiver<CoordinatorMessage> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        sut.handle(Event::WorkerConnected {
+            worker_id,
+            capacity,
+            sender,
+        });
+        receiver
+    }
+
+    fn received_job_ids(receiver: &mut mpsc::UnboundedReceiver<CoordinatorMessage>) -> Vec<u64> {
+        let mut result = Vec::new();
+
+        while let Ok(message) = receiver.try_recv() {
+            if let CoordinatorMessage::Job(job) = message {
+                result.push(job.id);
+            }
+        }
+
+        result
+    }
+
+    #[test]
+    fn reassign_jobs_of_lost_worker() {
+        let mut sut = Dispatcher::new(Duration::from_secs(1));
+        let mut receiver_0 = connect_worker(&mut sut, 0, 2);
+
+        let mut replies = Vec::new();
+
+        for id in 0..3 {
+            let (reply, reply_rx) = oneshot::channel();
+            sut.handle(Event::Submit {
+                job: make_job(id),
+                reply,
+                started: oneshot::channel().0,
+            });
+            replies.push(reply_rx);
+        }
+
+        sut.dispatch();
+        assert_eq!(received_job_ids(&mut receiver_0), vec![0, 1]);
+
+        let mut receiver_1 = connect_worker(&mut sut, 1, 1);
+        sut.handle(Event::WorkerDisconnected { worker_id: 0 });
+        sut.dispatch();
+        assert_eq!(received_job_ids(&mut receiver_1), vec![0]);
+
+        sut.handle(Event::WorkerMessage {
+            worker_id: 1,
+            message: WorkerMessage::Failed {
+                id: 0,
+                message: "failed".to_string(),
+            },
+        });
+        sut.dispatch();
+        assert_eq!(received_job_ids(&mut receiver_1), vec![1]);
+        assert_eq!(
+            replies[0].try_recv().unwrap(),
+            Err::<ObjFuncResult, _>("failed".to_string())
+        );
+
+        // late results from the lost worker are ignored
+        sut.handle(Event::WorkerMessage {
+            worker_id: 0,
+            message: WorkerMessage::Failed {
+                id: 1,
+                message: "failed".to_string(),
+            },
+        });
+        assert!(replies[1].try_recv().is_err());
+    }
+
+    #[test]
+    fn cancel_queued_job() {
+        let mut sut = Dispatcher::new(Duration::from_secs(1));
+
+        let (reply, _reply_rx) = oneshot::channel();
+        sut.handle(Event::Submit {
+            job: make_job(0),
+            reply,
+            started: oneshot::channel().0,
+        });
+        sut.handle(Event::Cancel { id: 0 });
+
+        let mut receiver = connect_worker(&mut sut, 0, 1);
+        sut.dispatch();
+
+        assert!(received_job_ids(&mut receiver).is_empty());
+        assert!(sut.jobs.is_empty());
+    }
+
+    #[test]
+    fn cancelled_job_occupies_capacity_until_ended() {
+        let mut sut = Dispatcher::new(Duration::from_secs(1));
+        let mut receiver = connect_worker(&mut sut, 0, 1);
+
+        let mut replies = Vec::new();
+
+        for id in 0..2 {
+            let (reply, reply_rx) = oneshot::channel();
+            sut.handle(Event::Submit {
+                job: make_job(id),
+                reply,
+                started: oneshot::channel().0,
+            });
+            replies.push(reply_rx);
+        }
+
+        sut.dispatch();
+        assert_eq!(received_job_ids(&mut receiver), vec![0]);
+
+        sut.handle(Event::Cancel { id: 0 });
+        sut.dispatch();
+        assert!(received_job_ids(&mut receiver).is_empty());
+
+        sut.handle(Event::WorkerMessage {
+            worker_id: 0,
+            message: WorkerMessage::Result {
+                id: 0,
+                result: ObjFuncResult::rejected().into(),
+            },
+        });
+        sut.dispatch();
+        assert_eq!(received_job_ids(&mut receiver), vec![1]);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::meta::MetaParamsWrapper;
+use crate::affinity;
+use crate::meta::{MetaParamsWrapper, RejectionReason};
 use std::time::Duration;
 
 #[derive(Clone)]
@@ -9,11 +10,20 @@ pub struct DetailedReportItem {
     pub input_val: serde_json::Value,
     pub seed: u64,
     pub obj_func_val: Option<f64>,
+    pub surrogate_prediction: Option<f64>,
+    pub budget: Option<f64>,
+    // false for results on a lower budget than the full one
+    pub full_fidelity: bool,
+    pub pruned: bool,
+    pub constraint_violation: Option<f64>,
+    pub rejection_reason: Option<RejectionReason>,
+    pub slot: Option<usize>,
+    pub cpu_set: Option<Vec<usize>>,
 }
 
 impl DetailedReportItem {
     pub fn get_csv_header_row() -> &'static str {
-        "individualId;evalTimeSeconds;metaParamsSource;crossoverProb;selectionPressure;mutationProb;mutationScale;inputVal;seed;objFuncVal\n"
+        "individualId;evalTimeSeconds;metaParamsSource;crossoverProb;selectionPressure;mutationProb;mutationScale;inputVal;seed;objFuncVal;surrogatePrediction;budget;pruned;constraintViolation;rejectionReason;slot;cpuSet\n"
     }
 
     pub fn to_csv_row(&self) -> String {
@@ -52,10 +62,29 @@ impl DetailedReportItem {
         let obj_func_val = self
             .obj_func_val
             .map(|val| val.to_string())
-            .unwrap_or_else(|| "".to_string());
+            .unwrap_or_default();
+        let surrogate_prediction = self
+            .surrogate_prediction
+            .map(|val| val.to_string())
+            .unwrap_or_default();
+        let budget = self.budget.map(|val| val.to_string()).unwrap_or_default();
+        let constraint_violation = self
+            .constraint_violation
+            .map(|val| val.to_string())
+            .unwrap_or_default();
+        let rejection_reason = self
+            .rejection_reason
+            .map(|reason| reason.to_string())
+            .unwrap_or_default();
+        let slot = self.slot.map(|slot| slot.to_string()).unwrap_or_default();
+        let cpu_set = self
+            .cpu_set
+            .as_deref()
+            .map(affinity::format_cpu_set)
+            .unwrap_or_default();
 
         format!(
-            "{};{};{};{};{};{};{};{};{};{}\n",
+            "{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};{};{}\n",
             self.individual_id,
             self.eval_time.as_secs_f64(),
             meta_params_source,
@@ -66,6 +95,13 @@ impl DetailedReportItem {
             input_val,
             self.seed,
             obj_func_val,
+            surrogate_prediction,
+            budget,
+            self.pruned,
+            constraint_violation,
+            rejection_reason,
+            slot,
+            cpu_set,
         )
     }
 }
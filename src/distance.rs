@@ -0,0 +1,270 @@
+use crate::spec;
+use crate::types::HashMap;
+use crate::value;
+use crate::value::Value;
+
+pub const CATEGORICAL_MISMATCH_COST: f64 = 1.0;
+pub const VARIANT_MISMATCH_COST: f64 = 1.0;
+pub const CONTAINER_MISMATCH_COST: f64 = 1.0;
+
+pub fn distance(spec: &spec::Spec, lhs: &Value, rhs: &Value) -> f64 {
+    node_distance(&spec.0, &lhs.0, &rhs.0)
+}
+
+fn node_distance(spec_node: &spec::Node, lhs: &value::Node, rhs: &value::Node) -> f64 {
+    match (spec_node, lhs, rhs) {
+        (spec::Node::Real { scale, .. }, value::Node::Real(lhs), value::Node::Real(rhs)) => {
+            (lhs - rhs).abs() / scale
+        }
+        (spec::Node::Int { scale, .. }, value::Node::Int(lhs), value::Node::Int(rhs)) => {
+            (lhs - rhs).abs() as f64 / scale
+        }
+        (spec::Node::Bool { .. }, value::Node::Bool(lhs), value::Node::Bool(rhs)) => {
+            categorical_distance(lhs, rhs)
+        }
+        (spec::Node::Enum { .. }, value::Node::Enum(lhs), value::Node::Enum(rhs)) => {
+            categorical_distance(lhs, rhs)
+        }
+        (spec::Node::Sub { map }, value::Node::Sub(lhs_map), value::Node::Sub(rhs_map)) => map
+            .iter()
+            .map(|(key, child_spec)| {
+                node_distance(
+                    child_spec,
+                    lhs_map.get(key).unwrap(),
+                    rhs_map.get(key).unwrap(),
+                )
+            })
+            .sum(),
+        (
+            spec::Node::Array { value_type, .. },
+            value::Node::Array(lhs_elements),
+            value::Node::Array(rhs_elements),
+        ) => lhs_elements
+            .iter()
+            .zip(rhs_elements.iter())
+            .map(|(lhs, rhs)| node_distance(value_type, lhs, rhs))
+            .sum(),
+        (
+            spec::Node::AnonMap { value_type, .. },
+            value::Node::AnonMap(lhs_map),
+            value::Node::AnonMap(rhs_map),
+        ) => anon_map_distance(value_type, lhs_map, rhs_map),
+        (
+            spec::Node::Variant { map, .. },
+            value::Node::Variant(lhs_name, lhs_value),
+            value::Node::Variant(rhs_name, rhs_value),
+        ) => {
+            if lhs_name == rhs_name {
+                node_distance(map.get(lhs_name).unwrap(), lhs_value, rhs_value)
+            } else {
+                VARIANT_MISMATCH_COST
+            }
+        }
+        (
+            spec::Node::Optional { value_type, .. },
+            value::Node::Optional(lhs),
+            value::Node::Optional(rhs),
+        ) => match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => node_distance(value_type, lhs, rhs),
+            (None, None) => 0.0,
+            _ => CONTAINER_MISMATCH_COST,
+        },
+        (spec::Node::Const, _, _) => 0.0,
+        _ => unreachable!(),
+    }
+}
+
+fn categorical_distance<T: PartialEq>(lhs: T, rhs: T) -> f64 {
+    if lhs == rhs {
+        0.0
+    } else {
+        CATEGORICAL_MISMATCH_COST
+    }
+}
+
+fn anon_map_distance(
+    value_type: &spec::Node,
+    lhs_map: &HashMap<usize, Box<value::Node>>,
+    rhs_map: &HashMap<usize, Box<value::Node>>,
+) -> f64 {
+    let lhs_side: f64 = lhs_map
+        .iter()
+        .map(|(key, lhs)| match rhs_map.get(key) {
+            Some(rhs) => node_distance(value_type, lhs, rhs),
+            None => CONTAINER_MISMATCH_COST,
+        })
+        .sum();
+
+    let rhs_only_count = rhs_map
+        .keys()
+        .filter(|key| !lhs_map.contains_key(key))
+        .count();
+
+    lhs_side + rhs_only_count as f64 * CONTAINER_MISMATCH_COST
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec_util::from_yaml_str;
+    use crate::value_util::from_json_str;
+    use float_cmp::assert_approx_eq;
+
+    fn compute(spec_str: &str, lhs_json: &str, rhs_json: &str) -> f64 {
+        let spec = from_yaml_str(spec_str).unwrap();
+        let lhs = from_json_str(lhs_json, &spec).unwrap();
+        let rhs = from_json_str(rhs_json, &spec).unwrap();
+
+        let result = distance(&spec, &lhs, &rhs);
+        assert_approx_eq!(f64, result, distance(&spec, &rhs, &lhs));
+        result
+    }
+
+    #[test]
+    fn numeric_leaves_normalized_by_scale() {
+        let spec_str = "
+        x:
+            type: real
+            init: 0.0
+            scale: 0.5
+        y:
+            type: int
+            init: 0
+            scale: 10
+        ";
+
+        let result = compute(spec_str, r#"{"x": 1.0, "y": 5}"#, r#"{"x": 0.0, "y": -5}"#);
+        assert_approx_eq!(f64, result, 3.0);
+    }
+
+    #[test]
+    fn identical_values() {
+        let spec_str = "
+        a:
+            type: bool
+            init: false
+        b:
+            type: enum
+            init: foo
+            values:
+            - foo
+            - bar
+        ";
+
+        let result = compute(
+            spec_str,
+            r#"{"a": true, "b": "bar"}"#,
+            r#"{"a": true, "b": "bar"}"#,
+        );
+        assert_approx_eq!(f64, result, 0.0);
+    }
+
+    #[test]
+    fn categorical_mismatch() {
+        let spec_str = "
+        a:
+            type: bool
+            init: false
+        b:
+            type: enum
+            init: foo
+            values:
+            - foo
+            - bar
+        ";
+
+        let result = compute(
+            spec_str,
+            r#"{"a": true, "b": "bar"}"#,
+            r#"{"a": false, "b": "foo"}"#,
+        );
+        assert_approx_eq!(f64, result, 2.0 * CATEGORICAL_MISMATCH_COST);
+    }
+
+    #[test]
+    fn variant() {
+        let spec_str = "
+        type: variant
+        init: foo
+        foo:
+            type: real
+            init: 0.0
+            scale: 2.0
+        bar:
+            type: const
+        ";
+
+        assert_approx_eq!(
+            f64,
+            compute(spec_str, r#"{"foo": 1.0}"#, r#"{"foo": 2.0}"#),
+            0.5
+        );
+        assert_approx_eq!(
+            f64,
+            compute(spec_str, r#"{"foo": 1.0}"#, r#"{"bar": null}"#),
+            VARIANT_MISMATCH_COST
+        );
+    }
+
+    #[test]
+    fn optional() {
+        let spec_str = "
+        type: optional
+        initPresent: true
+        valueType:
+            type: int
+            init: 0
+            scale: 1
+        ";
+
+        assert_approx_eq!(f64, compute(spec_str, "1", "3"), 2.0);
+        assert_approx_eq!(f64, compute(spec_str, "null", "null"), 0.0);
+        assert_approx_eq!(f64, compute(spec_str, "1", "null"), CONTAINER_MISMATCH_COST);
+    }
+
+    #[test]
+    fn anon_map() {
+        let spec = from_yaml_str(
+            "
+        type: anon map
+        initSize: 0
+        valueType:
+            type: real
+            init: 0.0
+            scale: 1.0
+        ",
+        )
+        .unwrap();
+
+        let lhs = Value(value::Node::AnonMap(HashMap::from_iter([
+            (0, Box::new(value::Node::Real(1.0))),
+            (1, Box::new(value::Node::Real(1.0))),
+        ])));
+
+        let rhs = Value(value::Node::AnonMap(HashMap::from_iter([
+            (1, Box::new(value::Node::Real(3.0))),
+            (2, Box::new(value::Node::Real(1.0))),
+            (3, Box::new(value::Node::Real(1.0))),
+        ])));
+
+        assert_approx_eq!(
+            f64,
+            distance(&spec, &lhs, &rhs),
+            2.0 + 3.0 * CONTAINER_MISMATCH_COST
+        );
+    }
+
+    #[test]
+    fn array() {
+        let spec_str = "
+        type: array
+        size: 2
+        valueType:
+            type: real
+            init: 0.0
+            scale: 1.0
+        ";
+
+        assert_approx_eq!(f64, compute(spec_str, "[0.0, 1.0]", "[1.0, 3.0]"), 3.0);
+    }
+}
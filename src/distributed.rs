@@ -0,0 +1,261 @@
+use crate::error::Error;
+use crate::meta::{ObjFuncResult, RejectionReason};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::UnixListener as StdUnixListener;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+pub const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+// Address the coordinator listens on and workers connect to, either host:port or unix:<path>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl From<&str> for Endpoint {
+    fn from(address: &str) -> Self {
+        match address.strip_prefix(UNIX_SOCKET_PREFIX) {
+            Some(path) => Self::Unix(path.into()),
+            None => Self::Tcp(address.to_string()),
+        }
+    }
+}
+
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{}", address),
+            Self::Unix(path) => write!(f, "{}{}", UNIX_SOCKET_PREFIX, path.display()),
+        }
+    }
+}
+
+// only sockets are removed, other files at the path make binding fail
+pub(crate) fn remove_stale_socket(path: &Path) {
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+pub(crate) type Reader = Box<dyn AsyncBufRead + Unpin + Send>;
+pub(crate) type Writer = Box<dyn AsyncWrite + Unpin + Send>;
+
+pub(crate) async fn connect(endpoint: &Endpoint) -> io::Result<(Reader, Writer)> {
+    match endpoint {
+        Endpoint::Tcp(address) => {
+            let stream = TcpStream::connect(address).await?;
+            stream.set_nodelay(true)?;
+            let (reader, writer) = stream.into_split();
+            Ok((Box::new(BufReader::new(reader)), Box::new(writer)))
+        }
+        Endpoint::Unix(path) => {
+            let (reader, writer) = UnixStream::connect(path).await?.into_split();
+            Ok((Box::new(BufReader::new(reader)), Box::new(writer)))
+        }
+    }
+}
+
+// Bound without a runtime, so that binding errors surface before the launch
+pub(crate) enum StdListener {
+    Tcp(std::net::TcpListener),
+    Unix(StdUnixListener),
+}
+
+pub(crate) enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+impl StdListener {
+    pub(crate) fn bind(endpoint: &Endpoint) -> io::Result<Self> {
+        let listener = match endpoint {
+            Endpoint::Tcp(address) => Self::Tcp(std::net::TcpListener::bind(address)?),
+            Endpoint::Unix(path) => Self::Unix(StdUnixListener::bind(path)?),
+        };
+
+        match &listener {
+            Self::Tcp(listener) => listener.set_nonblocking(true)?,
+            Self::Unix(listener) => listener.set_nonblocking(true)?,
+        }
+
+        Ok(listener)
+    }
+
+    pub(crate) fn local_endpoint(&self) -> io::Result<Endpoint> {
+        match self {
+            Self::Tcp(listener) => Ok(Endpoint::Tcp(listener.local_addr()?.to_string())),
+            Self::Unix(listener) => listener
+                .local_addr()?
+                .as_pathname()
+                .map(|path| Endpoint::Unix(path.to_owned()))
+                .ok_or_else(|| io::Error::other("unnamed Unix socket")),
+        }
+    }
+
+    // requires a runtime
+    pub(crate) fn into_async(self) -> io::Result<Listener> {
+        match self {
+            Self::Tcp(listener) => Ok(Listener::Tcp(TcpListener::from_std(listener)?)),
+            Self::Unix(listener) => Ok(Listener::Unix(UnixListener::from_std(listener)?)),
+        }
+    }
+}
+
+impl Listener {
+    pub(crate) async fn accept(&self) -> io::Result<(Reader, Writer)> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(BufReader::new(reader)), Box::new(writer)))
+            }
+            Self::Unix(listener) => {
+                let (stream, _) = listener.accept().await?;
+                let (reader, writer) = stream.into_split();
+                Ok((Box::new(BufReader::new(reader)), Box::new(writer)))
+            }
+        }
+    }
+}
+
+// Messages are sent as one JSON object per line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum WorkerMessage {
+    Hello { capacity: usize },
+    Heartbeat,
+    Result { id: u64, result: RemoteResult },
+    Failed { id: u64, message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum CoordinatorMessage {
+    Job(Job),
+    Cancel { id: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Job {
+    // unique per evaluation, unlike the individual id
+    pub id: u64,
+    pub individual_id: usize,
+    pub params: serde_json::Value,
+    pub seed: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteResult {
+    obj_func_val: Option<f64>,
+    #[serde(default)]
+    constraint_violation: Option<f64>,
+    #[serde(default)]
+    rejection_reason: Option<RejectionReason>,
+    #[serde(default)]
+    num_retries: usize,
+}
+
+impl From<ObjFuncResult> for RemoteResult {
+    fn from(result: ObjFuncResult) -> Self {
+        Self {
+            obj_func_val: result.obj_func_val,
+            constraint_violation: result.constraint_violation,
+            rejection_reason: result.rejection_reason,
+            num_retries: result.num_retries,
+        }
+    }
+}
+
+impl From<RemoteResult> for ObjFuncResult {
+    fn from(result: RemoteResult) -> Self {
+        Self {
+            obj_func_val: result.obj_func_val,
+            constraint_violation: result.constraint_violation,
+            rejection_reason: result.rejection_reason,
+            num_retries: result.num_retries,
+        }
+    }
+}
+
+pub(crate) async fn write_message<T: Serialize>(
+    writer: &mut Writer,
+    message: &T,
+) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line).await
+}
+
+pub(crate) fn parse_message<T: DeserializeOwned>(line: &str) -> Result<T, Error> {
+    serde_json::from_str(line).map_err(|_| Error::InvalidDistributedMessage(line.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_from_address() {
+        assert_eq!(
+            Endpoint::from("localhost:4000"),
+            Endpoint::Tcp("localhost:4000".to_string())
+        );
+        assert_eq!(
+            Endpoint::from("unix:/tmp/cambrian.sock"),
+            Endpoint::Unix("/tmp/cambrian.sock".into())
+        );
+        assert_eq!(
+            Endpoint::from("unix:/tmp/cambrian.sock").to_string(),
+            "unix:/tmp/cambrian.sock"
+        );
+    }
+
+    #[test]
+    fn message_format() {
+        let job = CoordinatorMessage::Job(Job {
+            id: 7,
+            individual_id: 3,
+            params: serde_json::json!({"x": 1.0}),
+            seed: 11,
+            budget: None,
+            sample_index: None,
+        });
+
+        assert_eq!(
+            serde_json::to_string(&job).unwrap(),
+            r#"{"type":"job","id":7,"individualId":3,"params":{"x":1.0},"seed":11}"#
+        );
+
+        let result: WorkerMessage =
+            parse_message(r#"{"type":"result","id":7,"result":{"objFuncVal":0.5}}"#).unwrap();
+        assert!(matches!(
+            result,
+            WorkerMessage::Result { id: 7, result } if result.obj_func_val == Some(0.5)
+        ));
+
+        assert!(matches!(
+            parse_message::<WorkerMessage>(r#"{"type":"bye"}"#),
+            Err(Error::InvalidDistributedMessage(_))
+        ));
+    }
+}
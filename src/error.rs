@@ -120,6 +120,10 @@ pub enum Error {
     },
     #[error("received non-finite objective function value")]
     ObjFuncValMustBeFinite,
+    #[error("objective function values must be strictly positive for the log transform")]
+    ObjFuncValMustBePositiveForLogTransform,
+    #[error("received non-finite constraint violation")]
+    ConstraintViolationMustBeFinite,
     #[error("no successfully evaluated individuals available")]
     NoIndividuals,
     #[error("client hung up")]
@@ -138,6 +142,63 @@ pub enum Error {
     TargetObjFuncValMustBeFinite,
     #[error("conflicting termination criteria")]
     ConflictingTerminationCriteria,
+    #[error("minimum improvement must be finite and non-negative")]
+    InvalidNoImprovementMinDelta,
+    #[error("signal termination criterion can only be used at top level")]
+    SignalCriterionNotComposable,
+    #[error("invalid termination expression: {0:?}")]
+    InvalidTerminationExpr(String),
+    #[error("unable to substitute placeholder in objective function arguments: {{{0}}}")]
+    UnresolvablePlaceholder(String),
+    #[error("placeholders without a value in this evaluation mode: {}", .0.iter().map(|name| format!("{{{}}}", name)).collect::<Vec<_>>().join(", "))]
+    UnavailablePlaceholders(Vec<String>),
+    #[error("unsupported config file format, must be JSON, YAML or TOML: {}", .0.display())]
+    UnsupportedConfigFormat(PathBuf),
+    #[error("Unable to read config template at path: {}, cause: {}", .path.display(), .source)]
+    UnableToReadConfigTemplate {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("config files cannot be rendered in batch mode")]
+    ConfigFileInBatchMode,
+    #[error("invalid config base document: {}", .0.display())]
+    InvalidConfigBase(PathBuf),
+    #[error("unable to render config file: {0}")]
+    UnableToRenderConfigFile(String),
+    #[error("unable to listen for workers on {endpoint}, cause: {source}")]
+    UnableToListenForWorkers {
+        endpoint: String,
+        source: std::io::Error,
+    },
+    #[error("unable to connect to coordinator at {endpoint}, cause: {source}")]
+    UnableToConnectToCoordinator {
+        endpoint: String,
+        source: std::io::Error,
+    },
+    #[error("invalid message: {0}")]
+    InvalidDistributedMessage(String),
+    #[error("evaluation failed on worker: {0}")]
+    WorkerEvaluationFailed(String),
+    #[error("coordinator stopped")]
+    CoordinatorStopped,
+    #[error("pruning is not supported with remote workers")]
+    PruningInDistributedMode,
+    #[error("the evaluation cache requires a file in stochastic mode, seeds never repeat within a run")]
+    EvalCacheWithoutFileInStochasticMode,
+    #[error("Unable to create control socket at path: {}, cause: {}", .path.display(), .source)]
+    UnableToCreateControlSocket {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Unable to connect to control socket at path: {}, cause: {}", .path.display(), .source)]
+    UnableToConnectToControlSocket {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid control message: {0}")]
+    InvalidControlMessage(String),
+    #[error("exit code {0} is assigned more than one outcome")]
+    OverlappingExitCodes(i32),
     #[error("output directory already exists")]
     OutputDirectoryAlreadyExists,
     #[error("failed to set signal handler")]
@@ -150,22 +211,56 @@ pub enum Error {
     ZeroSampleSize,
     #[error("number of concurrent objective function evaluations must be strictly positive")]
     ZeroNumConcurrent,
+    #[error("batch size must be strictly positive")]
+    ZeroBatchSize,
+    #[error("number of CPUs per slot must be strictly positive")]
+    ZeroCpusPerSlot,
+    #[error("CPU pinning requires {required} CPUs, but only {available} are available")]
+    NotEnoughCpus { required: usize, available: usize },
+    #[error("number of islands must be strictly positive")]
+    ZeroNumIslands,
+    #[error("migration interval must be strictly positive")]
+    ZeroMigrationInterval,
+    #[error("number of surrogate candidates must be strictly positive")]
+    ZeroSurrogateNumCandidates,
+    #[error("number of surrogate neighbors must be strictly positive")]
+    ZeroSurrogateNumNeighbors,
+    #[error("fidelity budgets must be finite and satisfy 0 < min budget <= max budget")]
+    InvalidFidelityBudgets,
+    #[error("reduction factor must be at least 2")]
+    ReductionFactorTooSmall,
+    #[error("pruning quantile must be in [0, 1]")]
+    InvalidPruningQuantile,
+    #[error("minimum number of intermediate reports for pruning must be strictly positive")]
+    ZeroPruningMinNumReports,
+    #[error("Unable to access evaluation cache file at path: {}, cause: {}", .path.display(), .source)]
+    UnableToAccessEvalCacheFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid evaluation cache file at path: {}, line {}", .path.display(), .line_number)]
+    InvalidEvalCacheFile { path: PathBuf, line_number: usize },
     #[error("Unable to create detailed reporting file at path: {}, cause: {}", .path.display(), .source)]
     UnableToCreateDetailedReportingFile {
         path: PathBuf,
         source: std::io::Error,
     },
+    #[error("Unable to create working directory at path: {}, cause: {}", .path.display(), .source)]
+    UnableToCreateWorkDir {
+        path: PathBuf,
+        source: std::io::Error,
+    },
 }
 
 #[derive(Debug)]
 pub struct ProcOutputWithObjFuncArg {
     pub obj_func_arg: OsString,
-    pub seed: u64,
+    pub seed: Option<u64>,
     pub output: Output,
 }
 
 impl ProcOutputWithObjFuncArg {
-    pub fn new(obj_func_arg: OsString, seed: u64, output: Output) -> Self {
+    pub fn new(obj_func_arg: OsString, seed: Option<u64>, output: Output) -> Self {
         Self {
             obj_func_arg,
             seed,
@@ -0,0 +1,296 @@
+use crate::error::Error;
+use crate::meta::ObjFuncResult;
+use crate::types::HashMap;
+use crate::value::Value;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tangram_finite::FiniteF64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheKey {
+    value_hash: u64,
+    seed: Option<u64>,
+    budget: Option<FiniteF64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheFileEntry {
+    #[serde(flatten)]
+    key: CacheKey,
+    obj_func_val: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    constraint_violation: Option<f64>,
+}
+
+pub struct EvalCache {
+    entries: HashMap<CacheKey, ObjFuncResult>,
+    keyed_by_seed: bool,
+    file: Option<(PathBuf, File)>,
+    num_hits: usize,
+    num_misses: usize,
+}
+
+impl EvalCache {
+    pub fn new(file_path: Option<&Path>, keyed_by_seed: bool) -> Result<Self, Error> {
+        let mut entries = HashMap::default();
+
+        let file = file_path
+            .map(|path| {
+                let to_error = |err| Error::UnableToAccessEvalCacheFile {
+                    path: path.to_owned(),
+                    source: err,
+                };
+
+                if path.exists() {
+                    load_entries(path, &mut entries)?;
+                    info!(
+                        "Loaded {} entries from evaluation cache file: {}",
+                        entries.len(),
+                        path.display()
+                    );
+                }
+
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(to_error)?;
+
+                Ok::<_, Error>((path.to_owned(), file))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            entries,
+            keyed_by_seed,
+            file,
+            num_hits: 0,
+            num_misses: 0,
+        })
+    }
+
+    fn make_key(&self, value: &Value, seed: u64, budget: Option<f64>) -> CacheKey {
+        CacheKey {
+            value_hash: value.canonical_hash(),
+            seed: self.keyed_by_seed.then_some(seed),
+            budget: budget.and_then(|budget| FiniteF64::new(budget).ok()),
+        }
+    }
+
+    pub fn lookup(
+        &mut self,
+        value: &Value,
+        seed: u64,
+        budget: Option<f64>,
+    ) -> Option<ObjFuncResult> {
+        let result = self
+            .entries
+            .get(&self.make_key(value, seed, budget))
+            .copied();
+
+        if result.is_some() {
+            self.num_hits += 1;
+        } else {
+            self.num_misses += 1;
+        }
+
+        result
+    }
+
+    pub fn insert(
+        &mut self,
+        value: &Value,
+        seed: u64,
+        budget: Option<f64>,
+        obj_func_result: ObjFuncResult,
+    ) -> Result<(), Error> {
+        let key = self.make_key(value, seed, budget);
+
+        if self.entries.insert(key, obj_func_result).is_none() {
+            if let Some((path, file)) = &mut self.file {
+                let entry = CacheFileEntry {
+                    key,
+                    obj_func_val: obj_func_result.obj_func_val,
+                    constraint_violation: obj_func_result.constraint_violation,
+                };
+                writeln!(file, "{}", serde_json::to_string(&entry).unwrap()).map_err(|err| {
+                    Error::UnableToAccessEvalCacheFile {
+                        path: path.to_owned(),
+                        source: err,
+                    }
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn num_hits(&self) -> usize {
+        self.num_hits
+    }
+
+    pub fn num_misses(&self) -> usize {
+        self.num_misses
+    }
+}
+
+fn load_entries(path: &Path, entries: &mut HashMap<CacheKey, ObjFuncResult>) -> Result<(), Error> {
+    let file = File::open(path).map_err(|err| Error::UnableToAccessEvalCacheFile {
+        path: path.to_owned(),
+        source: err,
+    })?;
+
+    for (line_idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| Error::UnableToAccessEvalCacheFile {
+            path: path.to_owned(),
+            source: err,
+        })?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry: CacheFileEntry =
+            serde_json::from_str(&line).map_err(|_| Error::InvalidEvalCacheFile {
+                path: path.to_owned(),
+                line_number: line_idx + 1,
+            })?;
+
+        entries.insert(
+            entry.key,
+            ObjFuncResult {
+                obj_func_val: entry.obj_func_val,
+                constraint_violation: entry.constraint_violation,
+                rejection_reason: None,
+                num_retries: 0,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Node;
+    use std::fs;
+
+    fn temp_file_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cambrian_{}_{}", name, std::process::id()));
+        fs::remove_file(&path).ok();
+        path
+    }
+
+    #[test]
+    fn hits_and_misses() {
+        let mut sut = EvalCache::new(None, false).unwrap();
+        let value = Value(Node::Bool(true));
+
+        assert_eq!(sut.lookup(&value, 0, None), None);
+        sut.insert(&value, 0, None, Some(0.5).into()).unwrap();
+        assert_eq!(sut.lookup(&value, 1, None), Some(Some(0.5).into()));
+
+        sut.insert(
+            &Value(Node::Bool(false)),
+            2,
+            None,
+            ObjFuncResult::rejected(),
+        )
+        .unwrap();
+        assert_eq!(
+            sut.lookup(&Value(Node::Bool(false)), 3, None),
+            Some(ObjFuncResult::rejected())
+        );
+
+        assert_eq!(sut.num_hits(), 2);
+        assert_eq!(sut.num_misses(), 1);
+    }
+
+    #[test]
+    fn keyed_by_seed() {
+        let mut sut = EvalCache::new(None, true).unwrap();
+        let value = Value(Node::Bool(true));
+
+        sut.insert(&value, 0, None, Some(0.5).into()).unwrap();
+        assert_eq!(sut.lookup(&value, 1, None), None);
+        assert_eq!(sut.lookup(&value, 0, None), Some(Some(0.5).into()));
+    }
+
+    #[test]
+    fn keyed_by_budget() {
+        let mut sut = EvalCache::new(None, false).unwrap();
+        let value = Value(Node::Bool(true));
+
+        sut.insert(&value, 0, Some(1.0), Some(0.5).into()).unwrap();
+        assert_eq!(sut.lookup(&value, 1, Some(3.0)), None);
+        assert_eq!(sut.lookup(&value, 1, None), None);
+        assert_eq!(sut.lookup(&value, 1, Some(1.0)), Some(Some(0.5).into()));
+    }
+
+    #[test]
+    fn file_round_trip() {
+        let path = temp_file_path("eval_cache_round_trip");
+        let infeasible_result = ObjFuncResult {
+            obj_func_val: None,
+            constraint_violation: Some(0.5),
+            rejection_reason: None,
+            num_retries: 0,
+        };
+
+        {
+            let mut sut = EvalCache::new(Some(&path), false).unwrap();
+            sut.insert(&Value(Node::Int(1)), 0, None, Some(1.5).into())
+                .unwrap();
+            sut.insert(
+                &Value(Node::Int(2)),
+                1,
+                Some(3.0),
+                ObjFuncResult::rejected(),
+            )
+            .unwrap();
+            sut.insert(&Value(Node::Int(3)), 2, None, infeasible_result)
+                .unwrap();
+        }
+
+        let mut sut = EvalCache::new(Some(&path), false).unwrap();
+        assert_eq!(
+            sut.lookup(&Value(Node::Int(1)), 5, None),
+            Some(Some(1.5).into())
+        );
+        assert_eq!(
+            sut.lookup(&Value(Node::Int(2)), 5, Some(3.0)),
+            Some(ObjFuncResult::rejected())
+        );
+        assert_eq!(
+            sut.lookup(&Value(Node::Int(3)), 5, None),
+            Some(infeasible_result)
+        );
+        assert_eq!(sut.lookup(&Value(Node::Int(4)), 5, None), None);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn invalid_file() {
+        let path = temp_file_path("eval_cache_invalid");
+        fs::write(
+            &path,
+            "{\"valueHash\": 1, \"seed\": null, \"budget\": null, \"objFuncVal\": 1.0}\nfoo\n",
+        )
+        .unwrap();
+
+        let result = EvalCache::new(Some(&path), false);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidEvalCacheFile { line_number: 2, .. })
+        ));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
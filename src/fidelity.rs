@@ -0,0 +1,226 @@
+use crate::algorithm::IndContext;
+use crate::meta::FidelityConfig;
+use log::{info, trace};
+use std::collections::{BTreeMap, BTreeSet};
+use tangram_finite::FiniteF64;
+
+type RungKey = (FiniteF64, usize);
+
+// Unpromoted individuals are only promoted after all better ones, so beyond this many per rung
+// the worst are dropped rather than kept for the whole run. Their results still count for ranking.
+const MAX_UNPROMOTED_PER_RUNG: usize = 256;
+
+#[derive(Default)]
+struct Rung {
+    completed: BTreeSet<RungKey>,
+    unpromoted: BTreeMap<RungKey, IndContext>,
+}
+
+// Asynchronous successive halving: every new individual starts on the lowest budget (rung 0).
+// Whenever an individual on rung k ranks within the top 1/reduction_factor of all results
+// seen on rung k so far, it is promoted to rung k + 1. Only results on the top rung (full
+// budget) are passed on to the algorithm.
+pub struct SuccessiveHalving {
+    budgets: Vec<f64>,
+    reduction_factor: usize,
+    rungs: Vec<Rung>,
+}
+
+impl SuccessiveHalving {
+    pub fn new(fidelity_config: &FidelityConfig) -> Self {
+        let budgets = compute_budgets(fidelity_config);
+        let rungs = (1..budgets.len()).map(|_| Rung::default()).collect();
+
+        info!("Multi-fidelity budgets: {:?}", budgets);
+
+        Self {
+            budgets,
+            reduction_factor: fidelity_config.reduction_factor,
+            rungs,
+        }
+    }
+
+    pub fn budget(&self, rung_idx: usize) -> f64 {
+        self.budgets[rung_idx]
+    }
+
+    pub fn top_rung_idx(&self) -> usize {
+        self.budgets.len() - 1
+    }
+
+    pub fn next_promotion(&mut self) -> Option<(IndContext, usize)> {
+        for rung_idx in (0..self.rungs.len()).rev() {
+            let rung = &mut self.rungs[rung_idx];
+            let num_to_promote = rung.completed.len() / self.reduction_factor;
+
+            if let Some(key) = rung.unpromoted.keys().next().copied() {
+                let rank = rung.completed.range(..key).count();
+
+                if rank < num_to_promote {
+                    let ind_ctx = rung.unpromoted.remove(&key).unwrap();
+                    let next_rung_idx = rung_idx + 1;
+
+                    info!(
+                        "Individual {}: promoted to budget {}",
+                        ind_ctx.id, self.budgets[next_rung_idx]
+                    );
+
+                    return Some((ind_ctx, next_rung_idx));
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn process_result(
+        &mut self,
+        ind_ctx: IndContext,
+        rung_idx: usize,
+        obj_func_val: Option<FiniteF64>,
+    ) -> Option<(IndContext, Option<FiniteF64>)> {
+        if rung_idx == self.top_rung_idx() {
+            return Some((ind_ctx, obj_func_val));
+        }
+
+        match obj_func_val {
+            Some(obj_func_val) => {
+                let key = (obj_func_val, ind_ctx.id);
+                let rung = &mut self.rungs[rung_idx];
+                rung.completed.insert(key);
+                rung.unpromoted.insert(key, ind_ctx);
+
+                if rung.unpromoted.len() > MAX_UNPROMOTED_PER_RUNG {
+                    let (_, dropped) = rung.unpromoted.pop_last().unwrap();
+                    trace!(
+                        "Individual {}: dropped from budget {}",
+                        dropped.id,
+                        self.budgets[rung_idx]
+                    );
+                }
+            }
+            None => info!(
+                "Individual {}: value rejected at budget {}",
+                ind_ctx.id, self.budgets[rung_idx]
+            ),
+        }
+
+        None
+    }
+}
+
+fn compute_budgets(fidelity_config: &FidelityConfig) -> Vec<f64> {
+    let eta = fidelity_config.reduction_factor as f64;
+    let ratio = fidelity_config.max_budget / fidelity_config.min_budget;
+
+    // tolerance guards against e.g. log(27) / log(3) evaluating to slightly below 3
+    let num_reductions = (ratio.ln() / eta.ln() + 1e-9).floor() as i32;
+
+    (0..=num_reductions)
+        .rev()
+        .map(|exponent| fidelity_config.max_budget / eta.powi(exponent))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::AlgoContext;
+    use crate::meta::AlgoConfigBuilder;
+    use crate::spec;
+
+    fn make_config(min_budget: f64, max_budget: f64, reduction_factor: usize) -> FidelityConfig {
+        FidelityConfig {
+            min_budget,
+            max_budget,
+            reduction_factor,
+        }
+    }
+
+    fn make_individuals(count: usize) -> Vec<IndContext> {
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let mut algo_ctx = AlgoContext::for_island(
+            spec::Spec(spec::Node::Bool { init: true }),
+            &algo_config,
+            None,
+            0,
+        );
+
+        (0..count).map(|_| algo_ctx.next_individual()).collect()
+    }
+
+    fn val(obj_func_val: f64) -> Option<FiniteF64> {
+        Some(FiniteF64::new(obj_func_val).unwrap())
+    }
+
+    #[test]
+    fn budgets() {
+        assert_eq!(
+            compute_budgets(&make_config(1.0, 27.0, 3)),
+            vec![1.0, 3.0, 9.0, 27.0]
+        );
+        assert_eq!(compute_budgets(&make_config(1.0, 20.0, 3)).len(), 3);
+        assert_eq!(compute_budgets(&make_config(5.0, 5.0, 2)), vec![5.0]);
+    }
+
+    #[test]
+    fn single_rung_passes_through() {
+        let mut sut = SuccessiveHalving::new(&make_config(1.0, 1.0, 3));
+        let ind_ctx = make_individuals(1).pop().unwrap();
+
+        assert_eq!(sut.top_rung_idx(), 0);
+        let result = sut.process_result(ind_ctx, 0, val(0.5));
+        assert!(matches!(result, Some((_, Some(obj_func_val))) if obj_func_val.get() == 0.5));
+    }
+
+    #[test]
+    fn promotion_of_top_fraction() {
+        let mut sut = SuccessiveHalving::new(&make_config(1.0, 9.0, 3));
+        let mut individuals = make_individuals(4).into_iter();
+
+        for obj_func_val in [0.3, 0.1] {
+            let result = sut.process_result(individuals.next().unwrap(), 0, val(obj_func_val));
+            assert!(result.is_none());
+        }
+
+        // fewer than 3 results on rung 0: nothing to promote yet
+        assert!(sut.next_promotion().is_none());
+
+        sut.process_result(individuals.next().unwrap(), 0, val(0.2));
+
+        let (promoted, rung_idx) = sut.next_promotion().unwrap();
+        assert_eq!(promoted.id, 1);
+        assert_eq!(rung_idx, 1);
+        assert_eq!(sut.budget(rung_idx), 3.0);
+        assert!(sut.next_promotion().is_none());
+
+        // a rejected individual is never promoted
+        sut.process_result(individuals.next().unwrap(), 0, None);
+        assert!(sut.next_promotion().is_none());
+
+        let result = sut.process_result(promoted, 1, val(0.15));
+        assert!(result.is_none());
+        assert!(sut.next_promotion().is_none());
+
+        // on the top rung, the result is handed back
+        let individual = make_individuals(1).pop().unwrap();
+        let result = sut.process_result(individual, 2, val(0.12));
+        assert!(matches!(result, Some((_, Some(obj_func_val))) if obj_func_val.get() == 0.12));
+    }
+
+    #[test]
+    fn worst_unpromoted_dropped() {
+        let mut sut = SuccessiveHalving::new(&make_config(1.0, 9.0, 3));
+        let num_individuals = MAX_UNPROMOTED_PER_RUNG + 1;
+
+        // in order of decreasing value, so that the first individual is the worst
+        for (idx, ind_ctx) in make_individuals(num_individuals).into_iter().enumerate() {
+            sut.process_result(ind_ctx, 0, val((num_individuals - idx) as f64));
+        }
+
+        let rung = &sut.rungs[0];
+        assert_eq!(rung.completed.len(), num_individuals);
+        assert_eq!(rung.unpromoted.len(), MAX_UNPROMOTED_PER_RUNG);
+        assert!(rung.unpromoted.values().all(|ind_ctx| ind_ctx.id != 0));
+    }
+}
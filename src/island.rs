@@ -0,0 +1,208 @@
+use crate::algorithm::{AlgoContext, IndContext};
+use crate::meta::{AlgoConfig, MigrationTopology};
+use crate::spec::Spec;
+use crate::value::Value;
+use log::info;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tangram_finite::FiniteF64;
+
+pub struct Archipelago {
+    islands: Vec<AlgoContext>,
+    next_island_idx: usize,
+    migration_interval: usize,
+    migration_topology: MigrationTopology,
+    count_since_migration: usize,
+    rng: StdRng,
+}
+
+impl Archipelago {
+    pub fn new(spec: Spec, algo_config: &AlgoConfig, explicit_init_value: Option<Value>) -> Self {
+        let islands = (0..algo_config.num_islands)
+            .map(|island_idx| {
+                AlgoContext::for_island(
+                    spec.clone(),
+                    algo_config,
+                    explicit_init_value.clone(),
+                    island_idx,
+                )
+            })
+            .collect();
+
+        Self {
+            islands,
+            next_island_idx: 0,
+            migration_interval: algo_config.migration_interval,
+            migration_topology: algo_config.migration_topology,
+            count_since_migration: 0,
+            rng: StdRng::seed_from_u64(algo_config.num_islands as u64),
+        }
+    }
+
+    pub fn next_individual(&mut self) -> IndContext {
+        let island_idx = self.next_island_idx;
+        self.next_island_idx = (self.next_island_idx + 1) % self.islands.len();
+        self.islands[island_idx].next_individual()
+    }
+
+    // injected values are evaluated on the first island
+    pub fn inject(&mut self, value: Value) {
+        self.islands[0].inject(value);
+    }
+
+    pub fn process_individual_eval(
+        &mut self,
+        ind_ctx: IndContext,
+        obj_func_val: Option<FiniteF64>,
+    ) {
+        let island_idx = self.island_idx_of(&ind_ctx);
+        self.islands[island_idx].process_individual_eval(ind_ctx, obj_func_val);
+
+        if obj_func_val.is_some() && self.islands.len() > 1 {
+            self.count_since_migration += 1;
+
+            if self.count_since_migration >= self.migration_interval {
+                self.count_since_migration = 0;
+                self.migrate();
+            }
+        }
+    }
+
+    pub fn process_infeasible_eval(
+        &mut self,
+        ind_ctx: IndContext,
+        constraint_violation: FiniteF64,
+    ) {
+        let island_idx = self.island_idx_of(&ind_ctx);
+        self.islands[island_idx].process_infeasible_eval(ind_ctx, constraint_violation);
+    }
+
+    pub fn best_seen_final(&self) -> Option<(FiniteF64, &IndContext)> {
+        self.islands
+            .iter()
+            .filter_map(AlgoContext::best_seen_final)
+            .min_by_key(|best_seen| best_seen.0)
+    }
+
+    pub fn island_idx_of(&self, ind_ctx: &IndContext) -> usize {
+        ind_ctx.id % self.islands.len()
+    }
+
+    fn migrate(&mut self) {
+        let num_islands = self.islands.len();
+
+        let migrants: Vec<_> = self
+            .islands
+            .iter()
+            .map(AlgoContext::best_final_for_migration)
+            .collect();
+
+        for (source_idx, migrant) in migrants.into_iter().enumerate() {
+            if let Some(migrant) = migrant {
+                let target_idx = match self.migration_topology {
+                    MigrationTopology::Ring => (source_idx + 1) % num_islands,
+                    MigrationTopology::Random => {
+                        let offset = self.rng.gen_range(1..num_islands);
+                        (source_idx + offset) % num_islands
+                    }
+                };
+
+                info!(
+                    "Migrating best individual from island {} to island {}",
+                    source_idx, target_idx
+                );
+
+                self.islands[target_idx].receive_migrant(migrant);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::AlgoConfigBuilder;
+    use crate::spec;
+
+    const TRIVIAL_SPEC: Spec = spec::Spec(spec::Node::Bool { init: true });
+
+    fn make_sut(num_islands: usize, migration_topology: MigrationTopology) -> Archipelago {
+        let algo_config = AlgoConfigBuilder::new()
+            .num_islands(num_islands)
+            .migration_interval(1)
+            .migration_topology(migration_topology)
+            .build()
+            .unwrap();
+
+        Archipelago::new(TRIVIAL_SPEC, &algo_config, None)
+    }
+
+    #[test]
+    fn round_robin_across_islands() {
+        let mut sut = make_sut(3, MigrationTopology::Ring);
+
+        let ids: Vec<usize> = (0..6).map(|_| sut.next_individual().id).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+
+        let island_idxs: Vec<usize> = (0..3)
+            .map(|_| {
+                let ind_ctx = sut.next_individual();
+                sut.island_idx_of(&ind_ctx)
+            })
+            .collect();
+        assert_eq!(island_idxs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn only_first_island_uses_initial_value() {
+        let mut sut = make_sut(2, MigrationTopology::Ring);
+
+        // the initial value is the only individual created without meta params
+        assert!(sut.next_individual().meta_params_used.is_none());
+        assert!(sut.next_individual().meta_params_used.is_some());
+    }
+
+    #[test]
+    fn ring_migration() {
+        let mut sut = make_sut(2, MigrationTopology::Ring);
+
+        let ind_0 = sut.next_individual();
+        let ind_1 = sut.next_individual();
+
+        sut.process_individual_eval(ind_0, Some(FiniteF64::new(0.1).unwrap()));
+
+        // island 0 sent its best individual to island 1
+        let (obj_func_val, ind_ctx) = sut.islands[1].best_seen_final().unwrap();
+        assert_eq!(obj_func_val.get(), 0.1);
+        assert_eq!(ind_ctx.value.0, crate::value::Node::Bool(true));
+
+        sut.process_individual_eval(ind_1, Some(FiniteF64::new(0.2).unwrap()));
+
+        // island 1 sent its best individual (the migrant) back, which is already present on
+        // island 0, so island 0 is unchanged
+        let (obj_func_val, _) = sut.islands[0].best_seen_final().unwrap();
+        assert_eq!(obj_func_val.get(), 0.1);
+        assert_eq!(sut.best_seen_final().unwrap().0.get(), 0.1);
+    }
+
+    #[test]
+    fn random_migration_targets_other_island() {
+        let mut sut = make_sut(2, MigrationTopology::Random);
+
+        let ind_0 = sut.next_individual();
+        sut.process_individual_eval(ind_0, Some(FiniteF64::new(0.3).unwrap()));
+
+        let (obj_func_val, _) = sut.islands[1].best_seen_final().unwrap();
+        assert_eq!(obj_func_val.get(), 0.3);
+    }
+
+    #[test]
+    fn rejected_evaluations_do_not_count_towards_migration() {
+        let mut sut = make_sut(2, MigrationTopology::Ring);
+
+        let ind_0 = sut.next_individual();
+        sut.process_individual_eval(ind_0, None);
+
+        assert!(sut.islands[1].best_seen_final().is_none());
+    }
+}
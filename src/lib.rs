@@ -1,21 +1,36 @@
+pub(crate) mod affinity;
 pub(crate) mod algorithm;
+pub(crate) mod arg_template;
 pub mod async_launch;
 pub(crate) mod common_util;
+pub mod config_template;
+pub mod control;
 pub(crate) mod controller;
+pub mod coordinator;
 pub mod crossover;
 pub(crate) mod detailed_report;
+pub mod distance;
+pub mod distributed;
 pub mod error;
+pub(crate) mod eval_cache;
+pub(crate) mod fidelity;
+pub(crate) mod island;
 pub mod message;
 pub mod meta;
 pub(crate) mod meta_adapt;
 pub mod mutation;
+pub(crate) mod objective;
+pub mod output_archive;
 pub(crate) mod path;
 pub mod process;
+pub mod pruning;
 pub(crate) mod rescaling;
 pub mod result;
+pub mod run_config;
 pub(crate) mod selection;
 pub mod spec;
 pub mod spec_util;
+pub(crate) mod surrogate;
 pub mod sync_launch;
 pub mod termination;
 #[cfg(test)]
@@ -23,3 +38,5 @@ pub(crate) mod testutil;
 pub(crate) mod types;
 pub mod value;
 pub mod value_util;
+pub mod work_dir;
+pub mod worker;
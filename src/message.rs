@@ -1,14 +1,99 @@
+use crate::meta::RejectionReason;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "camelCase")]
 pub enum Command {
     Terminate,
+    // stop scheduling new evaluations, in-flight ones are completed
+    Pause,
+    Resume,
+    #[serde(rename_all = "camelCase")]
+    SetNumConcurrent {
+        num_concurrent: usize,
+    },
+    // evaluate this value next, ahead of the values proposed by the algorithm
+    Inject {
+        value: serde_json::Value,
+    },
+    Status,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "camelCase")]
+pub enum Response {
+    Ok,
+    Status(Status),
+    Error { message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub paused: bool,
+    pub num_concurrent: usize,
+    pub best_seen_obj_func_val: Option<f64>,
+    pub best_seen: Option<serde_json::Value>,
+    pub num_obj_func_eval_completed: usize,
+    pub num_obj_func_eval_rejected: usize,
+    pub num_obj_func_eval_pruned: usize,
+    pub num_obj_func_eval_infeasible: usize,
+    pub num_obj_func_eval_limit_exceeded: usize,
+    pub num_obj_func_eval_rejected_by_exit_code: usize,
+    pub num_obj_func_eval_retries: usize,
+    pub in_flight: Vec<InFlightIndividual>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InFlightIndividual {
+    pub individual_id: usize,
+    pub value: serde_json::Value,
+    pub slot: usize,
+}
+
+// Progress events of a running optimization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
 pub enum Report {
+    #[serde(rename_all = "camelCase")]
+    IndividualEvalStarted {
+        individual_id: usize,
+        individual: serde_json::Value,
+        seed: u64,
+        slot: usize,
+    },
+    // also for infeasible individuals, which come with a constraint violation
+    #[serde(rename_all = "camelCase")]
     IndividualEvalCompleted {
-        obj_func_val: Option<f64>,
+        individual_id: usize,
+        obj_func_val: f64,
+        constraint_violation: Option<f64>,
+        individual: serde_json::Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndividualEvalRejected {
+        individual_id: usize,
+        rejection_reason: Option<RejectionReason>,
+        individual: serde_json::Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndividualEvalTimedOut {
+        individual_id: usize,
+        individual: serde_json::Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    IndividualEvalPruned {
+        individual_id: usize,
+        individual: serde_json::Value,
+    },
+    #[serde(rename_all = "camelCase")]
+    NewBestSeen {
+        individual_id: usize,
+        obj_func_val: f64,
         individual: serde_json::Value,
     },
+    // the reason is none if the run ended for lack of evaluations
+    #[serde(rename_all = "camelCase")]
+    Terminated { termination_reason: Option<String> },
 }
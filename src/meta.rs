@@ -1,8 +1,12 @@
 use crate::error::Error;
+use crate::pruning::IntermediateReporter;
 use async_broadcast::Receiver;
 use async_trait::async_trait;
 use enum_display_derive::Display;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub struct MetaParamsWrapper {
@@ -46,10 +50,88 @@ pub struct MutationParams {
     pub mutation_scale: f64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationTopology {
+    Ring,
+    Random,
+}
+
+#[derive(Debug, Clone)]
+pub struct FidelityConfig {
+    pub min_budget: f64,
+    pub max_budget: f64,
+    pub reduction_factor: usize,
+}
+
 #[derive(Debug, Clone)]
 pub struct AlgoConfig {
     pub individual_sample_size: usize,
     pub num_concurrent: usize,
+    pub num_islands: usize,
+    pub migration_interval: usize,
+    pub migration_topology: MigrationTopology,
+    pub crowding: bool,
+    pub eval_cache: bool,
+    pub eval_cache_file: Option<PathBuf>,
+    pub surrogate_num_candidates: Option<usize>,
+    pub surrogate_num_neighbors: usize,
+    pub fidelity: Option<FidelityConfig>,
+    pub pruning: Option<PruningConfig>,
+    pub batch_size: Option<usize>,
+    pub objective: ObjectiveConfig,
+    pub drain_grace_period: Option<Duration>,
+    pub cpu_pinning: Option<CpuPinningConfig>,
+    pub control_socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ObjFuncResult {
+    pub obj_func_val: Option<f64>,
+    pub constraint_violation: Option<f64>,
+    pub rejection_reason: Option<RejectionReason>,
+    // number of times the evaluation was retried before this result was obtained
+    pub num_retries: usize,
+}
+
+impl ObjFuncResult {
+    pub fn rejected() -> Self {
+        Self::default()
+    }
+
+    pub fn rejected_with_reason(rejection_reason: RejectionReason) -> Self {
+        Self {
+            rejection_reason: Some(rejection_reason),
+            ..Self::default()
+        }
+    }
+
+    pub fn infeasible(obj_func_val: f64, constraint_violation: f64) -> Self {
+        Self {
+            obj_func_val: Some(obj_func_val),
+            constraint_violation: Some(constraint_violation),
+            ..Self::default()
+        }
+    }
+}
+
+impl From<Option<f64>> for ObjFuncResult {
+    fn from(obj_func_val: Option<f64>) -> Self {
+        Self {
+            obj_func_val,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum RejectionReason {
+    AddressSpaceLimitExceeded,
+    CpuTimeLimitExceeded,
+    OpenFilesLimitExceeded,
+    ExitCode,
+    Timeout,
+    // e.g. a value that is not positive with the log transform
+    ObjectiveTransformUndefined,
 }
 
 #[async_trait]
@@ -58,13 +140,67 @@ pub trait AsyncObjectiveFunction: Sync {
         &self,
         value: serde_json::Value,
         abort_signal_recv: Receiver<()>,
-        seed: u64,
-        individual_id: usize,
-    ) -> Result<Option<f64>, Error>;
+        eval_ctx: &EvalContext,
+        intermediate_reporter: &IntermediateReporter,
+    ) -> Result<ObjFuncResult, Error>;
+
+    async fn evaluate_batch(
+        &self,
+        batch_items: Vec<BatchItem>,
+        abort_signal_recv: Receiver<()>,
+    ) -> Result<Vec<ObjFuncResult>, Error> {
+        let intermediate_reporter = IntermediateReporter::disabled();
+
+        let eval_futures = batch_items.iter().map(|batch_item| {
+            self.evaluate(
+                batch_item.value.clone(),
+                abort_signal_recv.clone(),
+                &batch_item.eval_ctx,
+                &intermediate_reporter,
+            )
+        });
+
+        futures::future::try_join_all(eval_futures).await
+    }
+
+    // checks before the launch that the objective function can be evaluated as configured
+    fn validate(&self, _algo_config: &AlgoConfig) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+pub struct BatchItem {
+    pub value: serde_json::Value,
+    pub eval_ctx: EvalContext,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalContext {
+    pub seed: u64,
+    pub budget: Option<f64>,
+    pub individual_id: usize,
+    // only set in stochastic mode
+    pub sample_index: Option<usize>,
+    // concurrency slot the evaluation runs in, and the CPUs pinned to that slot, if any
+    pub slot: usize,
+    pub cpu_set: Option<Vec<usize>>,
 }
 
 pub trait ObjectiveFunction: Sync + Send + 'static {
     fn evaluate(&self, value: serde_json::Value) -> Option<f64>;
+
+    fn evaluate_with_budget(&self, value: serde_json::Value, _budget: Option<f64>) -> Option<f64> {
+        self.evaluate(value)
+    }
+
+    // overridden by objective functions that report constraint violations
+    fn evaluate_with_constraints(
+        &self,
+        value: serde_json::Value,
+        budget: Option<f64>,
+    ) -> ObjFuncResult {
+        self.evaluate_with_budget(value, budget).into()
+    }
 }
 
 pub struct ObjectiveFunctionImpl<F> {
@@ -87,12 +223,90 @@ where
     ObjectiveFunctionImpl { obj_func: f }
 }
 
+pub struct ConstrainedObjectiveFunctionImpl<F> {
+    obj_func: F,
+}
+
+impl<F> ObjectiveFunction for ConstrainedObjectiveFunctionImpl<F>
+where
+    F: Fn(serde_json::Value) -> ObjFuncResult + Send + Sync + 'static,
+{
+    fn evaluate(&self, value: serde_json::Value) -> Option<f64> {
+        (self.obj_func)(value).obj_func_val
+    }
+
+    fn evaluate_with_constraints(
+        &self,
+        value: serde_json::Value,
+        _budget: Option<f64>,
+    ) -> ObjFuncResult {
+        (self.obj_func)(value)
+    }
+}
+
+pub fn make_constrained_obj_func<F>(f: F) -> ConstrainedObjectiveFunctionImpl<F>
+where
+    F: Fn(serde_json::Value) -> ObjFuncResult,
+{
+    ConstrainedObjectiveFunctionImpl { obj_func: f }
+}
+
 pub struct AlgoConfigBuilder {
     individual_sample_size: Option<usize>,
     num_concurrent: Option<usize>,
+    num_islands: Option<usize>,
+    migration_interval: Option<usize>,
+    migration_topology: Option<MigrationTopology>,
+    crowding: bool,
+    eval_cache: bool,
+    eval_cache_file: Option<PathBuf>,
+    surrogate_num_candidates: Option<usize>,
+    surrogate_num_neighbors: Option<usize>,
+    fidelity: Option<FidelityConfig>,
+    pruning: Option<PruningConfig>,
+    batch_size: Option<usize>,
+    objective: ObjectiveConfig,
+    drain_grace_period: Option<Duration>,
+    cpu_pinning: Option<CpuPinningConfig>,
+    control_socket: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectiveTransform {
+    #[default]
+    Identity,
+    Log,
+    // percentile ranks among the values of the current population, recomputed as it changes
+    Rank,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObjectiveConfig {
+    pub maximize: bool,
+    pub transform: ObjectiveTransform,
+}
+
+#[derive(Debug, Clone)]
+pub struct CpuPinningConfig {
+    pub cpus_per_slot: usize,
+    // sync launches confine the calling thread to the reserved CPUs until the launch ends,
+    // async ones only keep them out of the slots
+    pub num_reserved_cpus: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct PruningConfig {
+    pub quantile: f64,
+    pub min_num_reports: usize,
 }
 
 const DEFAULT_IND_SAMPLE_SIZE: usize = 1;
+const DEFAULT_MIGRATION_INTERVAL: usize = 100;
+const DEFAULT_SURROGATE_NUM_NEIGHBORS: usize = 5;
+pub const DEFAULT_REDUCTION_FACTOR: usize = 3;
+pub const DEFAULT_PRUNING_MIN_NUM_REPORTS: usize = 5;
 
 impl Default for AlgoConfigBuilder {
     fn default() -> Self {
@@ -111,10 +325,126 @@ impl AlgoConfigBuilder {
         self
     }
 
+    pub fn num_islands(&mut self, num_islands: usize) -> &mut Self {
+        self.num_islands = Some(num_islands);
+        self
+    }
+
+    pub fn migration_interval(&mut self, migration_interval: usize) -> &mut Self {
+        self.migration_interval = Some(migration_interval);
+        self
+    }
+
+    pub fn migration_topology(&mut self, migration_topology: MigrationTopology) -> &mut Self {
+        self.migration_topology = Some(migration_topology);
+        self
+    }
+
+    pub fn crowding(&mut self, crowding: bool) -> &mut Self {
+        self.crowding = crowding;
+        self
+    }
+
+    pub fn eval_cache(&mut self, eval_cache: bool) -> &mut Self {
+        self.eval_cache = eval_cache;
+        self
+    }
+
+    pub fn eval_cache_file(&mut self, eval_cache_file: PathBuf) -> &mut Self {
+        self.eval_cache = true;
+        self.eval_cache_file = Some(eval_cache_file);
+        self
+    }
+
+    pub fn surrogate_num_candidates(&mut self, surrogate_num_candidates: usize) -> &mut Self {
+        self.surrogate_num_candidates = Some(surrogate_num_candidates);
+        self
+    }
+
+    pub fn surrogate_num_neighbors(&mut self, surrogate_num_neighbors: usize) -> &mut Self {
+        self.surrogate_num_neighbors = Some(surrogate_num_neighbors);
+        self
+    }
+
+    pub fn fidelity(
+        &mut self,
+        min_budget: f64,
+        max_budget: f64,
+        reduction_factor: usize,
+    ) -> &mut Self {
+        self.fidelity = Some(FidelityConfig {
+            min_budget,
+            max_budget,
+            reduction_factor,
+        });
+        self
+    }
+
+    pub fn pruning(&mut self, quantile: f64, min_num_reports: usize) -> &mut Self {
+        self.pruning = Some(PruningConfig {
+            quantile,
+            min_num_reports,
+        });
+        self
+    }
+
+    pub fn batch_size(&mut self, batch_size: usize) -> &mut Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    pub fn maximize(&mut self, maximize: bool) -> &mut Self {
+        self.objective.maximize = maximize;
+        self
+    }
+
+    pub fn objective_transform(&mut self, objective_transform: ObjectiveTransform) -> &mut Self {
+        self.objective.transform = objective_transform;
+        self
+    }
+
+    pub fn objective(&mut self, objective: ObjectiveConfig) -> &mut Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn drain_grace_period(&mut self, drain_grace_period: Duration) -> &mut Self {
+        self.drain_grace_period = Some(drain_grace_period);
+        self
+    }
+
+    pub fn cpu_pinning(&mut self, cpus_per_slot: usize, num_reserved_cpus: usize) -> &mut Self {
+        self.cpu_pinning = Some(CpuPinningConfig {
+            cpus_per_slot,
+            num_reserved_cpus,
+        });
+        self
+    }
+
+    pub fn control_socket(&mut self, control_socket: PathBuf) -> &mut Self {
+        self.control_socket = Some(control_socket);
+        self
+    }
+
     pub fn new() -> Self {
         Self {
             individual_sample_size: None,
             num_concurrent: None,
+            num_islands: None,
+            migration_interval: None,
+            migration_topology: None,
+            crowding: false,
+            eval_cache: false,
+            eval_cache_file: None,
+            surrogate_num_candidates: None,
+            surrogate_num_neighbors: None,
+            fidelity: None,
+            pruning: None,
+            batch_size: None,
+            objective: ObjectiveConfig::default(),
+            drain_grace_period: None,
+            cpu_pinning: None,
+            control_socket: None,
         }
     }
 
@@ -124,6 +454,25 @@ impl AlgoConfigBuilder {
                 .individual_sample_size
                 .unwrap_or(DEFAULT_IND_SAMPLE_SIZE),
             num_concurrent: self.num_concurrent.unwrap_or(1),
+            num_islands: self.num_islands.unwrap_or(1),
+            migration_interval: self
+                .migration_interval
+                .unwrap_or(DEFAULT_MIGRATION_INTERVAL),
+            migration_topology: self.migration_topology.unwrap_or(MigrationTopology::Ring),
+            crowding: self.crowding,
+            eval_cache: self.eval_cache,
+            eval_cache_file: self.eval_cache_file.clone(),
+            surrogate_num_candidates: self.surrogate_num_candidates,
+            surrogate_num_neighbors: self
+                .surrogate_num_neighbors
+                .unwrap_or(DEFAULT_SURROGATE_NUM_NEIGHBORS),
+            fidelity: self.fidelity.clone(),
+            pruning: self.pruning.clone(),
+            batch_size: self.batch_size,
+            objective: self.objective.clone(),
+            drain_grace_period: self.drain_grace_period,
+            cpu_pinning: self.cpu_pinning.clone(),
+            control_socket: self.control_socket.clone(),
         };
 
         if algo_config.individual_sample_size == 0 {
@@ -134,6 +483,64 @@ impl AlgoConfigBuilder {
             return Err(Error::ZeroNumConcurrent);
         }
 
+        if algo_config.num_islands == 0 {
+            return Err(Error::ZeroNumIslands);
+        }
+
+        if algo_config.batch_size == Some(0) {
+            return Err(Error::ZeroBatchSize);
+        }
+
+        if algo_config.migration_interval == 0 {
+            return Err(Error::ZeroMigrationInterval);
+        }
+
+        if algo_config.surrogate_num_candidates == Some(0) {
+            return Err(Error::ZeroSurrogateNumCandidates);
+        }
+
+        // results are cached per seed, which are handed out sequentially from the start of a run
+        if algo_config.eval_cache
+            && algo_config.eval_cache_file.is_none()
+            && algo_config.individual_sample_size > 1
+        {
+            return Err(Error::EvalCacheWithoutFileInStochasticMode);
+        }
+
+        if algo_config.surrogate_num_neighbors == 0 {
+            return Err(Error::ZeroSurrogateNumNeighbors);
+        }
+
+        if let Some(ref fidelity) = algo_config.fidelity {
+            if !fidelity.min_budget.is_finite()
+                || !fidelity.max_budget.is_finite()
+                || fidelity.min_budget <= 0.0
+                || fidelity.min_budget > fidelity.max_budget
+            {
+                return Err(Error::InvalidFidelityBudgets);
+            }
+
+            if fidelity.reduction_factor < 2 {
+                return Err(Error::ReductionFactorTooSmall);
+            }
+        }
+
+        if let Some(ref pruning) = algo_config.pruning {
+            if !(0.0..=1.0).contains(&pruning.quantile) {
+                return Err(Error::InvalidPruningQuantile);
+            }
+
+            if pruning.min_num_reports == 0 {
+                return Err(Error::ZeroPruningMinNumReports);
+            }
+        }
+
+        if let Some(ref cpu_pinning) = algo_config.cpu_pinning {
+            if cpu_pinning.cpus_per_slot == 0 {
+                return Err(Error::ZeroCpusPerSlot);
+            }
+        }
+
         Ok(algo_config)
     }
 }
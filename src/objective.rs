@@ -0,0 +1,96 @@
+use crate::error::Error;
+use crate::meta::{ObjectiveConfig, ObjectiveTransform};
+use tangram_finite::FiniteF64;
+
+// Maps objective function values as reported by the objective function to the values the
+// algorithm minimizes. Transforms are strictly monotonic, so the best individual is the same in
+// both spaces. Ranks depend on the current population, so they are left to the algorithm and
+// values are only oriented here.
+pub struct ObjectiveTransformer {
+    maximize: bool,
+    transform: ObjectiveTransform,
+}
+
+impl ObjectiveTransformer {
+    pub fn new(objective_config: &ObjectiveConfig) -> Self {
+        Self {
+            maximize: objective_config.maximize,
+            transform: objective_config.transform,
+        }
+    }
+
+    pub fn transform(&self, obj_func_val: FiniteF64) -> Result<FiniteF64, Error> {
+        let obj_func_val = obj_func_val.get();
+
+        let transformed = match self.transform {
+            ObjectiveTransform::Log if obj_func_val <= 0.0 => {
+                return Err(Error::ObjFuncValMustBePositiveForLogTransform)
+            }
+            ObjectiveTransform::Log => obj_func_val.ln(),
+            ObjectiveTransform::Identity | ObjectiveTransform::Rank => obj_func_val,
+        };
+
+        Ok(FiniteF64::new(self.orient(transformed)).unwrap())
+    }
+
+    // lower is better for oriented values
+    pub fn orient(&self, obj_func_val: f64) -> f64 {
+        if self.maximize {
+            -obj_func_val
+        } else {
+            obj_func_val
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+
+    fn make_sut(maximize: bool, transform: ObjectiveTransform) -> ObjectiveTransformer {
+        ObjectiveTransformer::new(&ObjectiveConfig {
+            maximize,
+            transform,
+        })
+    }
+
+    fn transform(sut: &ObjectiveTransformer, obj_func_val: f64) -> f64 {
+        sut.transform(FiniteF64::new(obj_func_val).unwrap())
+            .unwrap()
+            .get()
+    }
+
+    #[test]
+    fn identity() {
+        let sut = make_sut(false, ObjectiveTransform::Identity);
+        assert_eq!(transform(&sut, -2.5), -2.5);
+        assert_eq!(sut.orient(-2.5), -2.5);
+    }
+
+    #[test]
+    fn maximize() {
+        let sut = make_sut(true, ObjectiveTransform::Identity);
+        assert_eq!(transform(&sut, 0.9), -0.9);
+        assert_eq!(sut.orient(0.95), -0.95);
+    }
+
+    #[test]
+    fn rank_only_orients() {
+        let sut = make_sut(true, ObjectiveTransform::Rank);
+        assert_eq!(transform(&sut, 0.9), -0.9);
+        assert_eq!(transform(&sut, 0.95), -0.95);
+    }
+
+    #[test]
+    fn log() {
+        let sut = make_sut(true, ObjectiveTransform::Log);
+        assert_approx_eq!(f64, transform(&sut, std::f64::consts::E), -1.0);
+
+        let result = sut.transform(FiniteF64::new(0.0).unwrap());
+        assert!(matches!(
+            result,
+            Err(Error::ObjFuncValMustBePositiveForLogTransform)
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use crate::error::Error;
+use crate::work_dir::WorkDirs;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub const STDOUT_FILE_NAME: &str = "stdout.gz";
+pub const STDERR_FILE_NAME: &str = "stderr.gz";
+
+// Gzipped stdout and stderr of the objective function processes, in one directory per
+// evaluation. Retention works as for working directories.
+#[derive(Debug)]
+pub struct OutputArchive {
+    log_dirs: WorkDirs,
+    // per stream, only the end of longer output is kept
+    max_bytes: Option<usize>,
+}
+
+impl OutputArchive {
+    pub fn new(
+        root: &Path,
+        keep_best: Option<usize>,
+        max_bytes: Option<usize>,
+        maximize: bool,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            log_dirs: WorkDirs::new(root, keep_best, maximize)?,
+            max_bytes,
+        })
+    }
+
+    pub(crate) fn log_dirs(&self) -> &WorkDirs {
+        &self.log_dirs
+    }
+
+    pub(crate) async fn create(&self, name: &str) -> Result<PathBuf, Error> {
+        self.log_dirs.create(name).await
+    }
+
+    // output of an earlier attempt of the same evaluation is overwritten
+    pub(crate) async fn write(
+        &self,
+        log_dir: &Path,
+        stdout: &[u8],
+        stderr: &[u8],
+    ) -> Result<(), Error> {
+        for (file_name, data) in [(STDOUT_FILE_NAME, stdout), (STDERR_FILE_NAME, stderr)] {
+            let compressed = compress(truncate(data, self.max_bytes))?;
+            tokio::fs::write(log_dir.join(file_name), compressed).await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn truncate(data: &[u8], max_bytes: Option<usize>) -> &[u8] {
+    match max_bytes {
+        Some(max_bytes) if data.len() > max_bytes => &data[data.len() - max_bytes..],
+        _ => data,
+    }
+}
+
+fn compress(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn decompress(path: &Path) -> String {
+        let mut result = String::new();
+        GzDecoder::new(std::fs::File::open(path).unwrap())
+            .read_to_string(&mut result)
+            .unwrap();
+        result
+    }
+
+    #[tokio::test]
+    async fn write() {
+        let root =
+            std::env::temp_dir().join(format!("cambrian_output_archive_{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+
+        let sut = OutputArchive::new(&root, None, Some(6), false).unwrap();
+        let log_dir = sut.create("1-2").await.unwrap();
+
+        sut.write(&log_dir, b"loading\n{}\n", b"warn\n")
+            .await
+            .unwrap();
+
+        assert_eq!(decompress(&log_dir.join(STDOUT_FILE_NAME)), "ng\n{}\n");
+        assert_eq!(decompress(&log_dir.join(STDERR_FILE_NAME)), "warn\n");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
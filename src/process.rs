@@ -1,23 +1,310 @@
+use crate::affinity;
+use crate::arg_template::{self, ArgContext, AvailablePlaceholders};
+use crate::config_template::{ConfigFileDef, ConfigTemplate, CONFIG_FILE_ENV_VAR};
 use crate::error::ProcOutputWithObjFuncArg;
-use crate::{error::Error, meta::AsyncObjectiveFunction};
+use crate::output_archive::OutputArchive;
+use crate::pruning::IntermediateReporter;
+use crate::types::HashMap;
+use crate::work_dir::{WorkDirs, WORK_DIR_ENV_VAR};
+use crate::{
+    error::Error,
+    meta::{
+        AlgoConfig, AsyncObjectiveFunction, BatchItem, EvalContext, ObjFuncResult, RejectionReason,
+    },
+};
 use async_trait::async_trait;
 use command_group::{AsyncCommandGroup, AsyncGroupChild};
 use futures::future::Either;
+use itertools::Itertools;
 use log::{info, trace};
 use nix::errno::Errno;
+use nix::sched::sched_setaffinity;
+use nix::sys::resource::{setrlimit, Resource};
 use nix::sys::signal::{self, Signal};
-use nix::sys::wait;
-use nix::unistd::Pid;
-use serde::Deserialize;
+use nix::sys::wait::{self, WaitPidFlag, WaitStatus};
+use nix::unistd::{sysconf, Pid, SysconfVar};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
-use std::process::Stdio;
+use std::io;
+use std::mem::MaybeUninit;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{self, Path, PathBuf};
+use std::process::{ExitStatus, Output, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use std::{ffi::OsString, time::Duration};
-use tokio::process::Command;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::{ChildStderr, Command};
+
+pub const DEFAULT_KILL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+const REAP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// for the rest of the error output of a stopped process
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
+// grace between the soft limit, at which SIGXCPU is sent, and the hard limit, at which the
+// process is killed
+const CPU_TIME_HARD_LIMIT_MARGIN_SECS: u64 = 1;
+
+const OUT_OF_MEMORY_MESSAGES: &[&str] = &[
+    "MemoryError",
+    "bad_alloc",
+    "memory allocation of",
+    "memory exhausted",
+    "Cannot allocate memory",
+    "out of memory",
+];
+
+const TOO_MANY_OPEN_FILES_MESSAGE: &str = "Too many open files";
+
+pub const INDIVIDUAL_ID_ENV_VAR: &str = "CAMBRIAN_INDIVIDUAL_ID";
+pub const SEED_ENV_VAR: &str = "CAMBRIAN_SEED";
+pub const SAMPLE_INDEX_ENV_VAR: &str = "CAMBRIAN_SAMPLE_INDEX";
+pub const SLOT_ENV_VAR: &str = "CAMBRIAN_SLOT";
+pub const OUT_DIR_ENV_VAR: &str = "CAMBRIAN_OUT_DIR";
+// Unix time in seconds at which the process is stopped, if a timeout is set
+pub const DEADLINE_ENV_VAR: &str = "CAMBRIAN_DEADLINE";
+pub const RESULT_FILE_ENV_VAR: &str = "CAMBRIAN_RESULT_FILE";
+const RESULT_FILE_NAME: &str = "result.json";
 
 pub struct ObjFuncProcessDef {
     pub program: OsString,
     pub args: Vec<OsString>,
     pub kill_obj_func_after: Option<Duration>,
+    pub kill_signal: Signal,
+    pub kill_grace_period: Duration,
+    pub resource_limits: ResourceLimits,
+    pub work_dirs: Option<WorkDirs>,
+    pub out_dir: Option<PathBuf>,
+    // extra environment variables set for each process
+    pub env: Vec<(OsString, OsString)>,
+    pub config_file: Option<ConfigFileDef>,
+    pub result_source: ResultSource,
+    pub exit_codes: ExitCodes,
+    pub output_archive: Option<OutputArchive>,
+}
+
+// Where the result of an objective function process is taken from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ResultSource {
+    // the whole standard output, apart from intermediate reports
+    #[default]
+    Stdout,
+    // the last non-empty line of the standard output
+    LastLine,
+    // the last line of the standard output starting with the prefix, which is stripped
+    PrefixedLine(String),
+    // a JSON file written by the process to the path in CAMBRIAN_RESULT_FILE
+    File,
+}
+
+// Outcomes of objective function processes by exit code. Processes exiting with any other code,
+// or killed by a signal, fail the run.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct ExitCodes {
+    // the result is taken from the output
+    pub parse: Vec<i32>,
+    pub reject: Vec<i32>,
+    // the process is run again, at most max_retries times per evaluation
+    pub retry: Vec<i32>,
+    pub max_retries: usize,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        Self {
+            parse: vec![0],
+            reject: Vec::new(),
+            retry: Vec::new(),
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitCodeOutcome {
+    Parse,
+    Reject,
+    Retry,
+}
+
+impl ExitCodes {
+    // each code may have one outcome only, repeating a code within one outcome is harmless
+    pub fn validate(&self) -> Result<(), Error> {
+        match [&self.parse, &self.reject, &self.retry]
+            .into_iter()
+            .flat_map(|codes| codes.iter().unique())
+            .duplicates()
+            .next()
+        {
+            Some(code) => Err(Error::OverlappingExitCodes(*code)),
+            None => Ok(()),
+        }
+    }
+
+    fn outcome(&self, status: ExitStatus) -> Option<ExitCodeOutcome> {
+        let code = status.code()?;
+
+        if self.parse.contains(&code) {
+            Some(ExitCodeOutcome::Parse)
+        } else if self.reject.contains(&code) {
+            Some(ExitCodeOutcome::Reject)
+        } else if self.retry.contains(&code) {
+            Some(ExitCodeOutcome::Retry)
+        } else {
+            None
+        }
+    }
+}
+
+enum ChildResult<T> {
+    Parsed(T),
+    Rejected,
+    Retry,
+}
+
+impl ResultSource {
+    fn extract<'a>(&self, stdout: &'a [u8]) -> Option<&'a [u8]> {
+        let mut lines_from_last = stdout.split(|byte| *byte == b'\n').rev();
+
+        match self {
+            Self::Stdout | Self::File => Some(stdout),
+            Self::LastLine => lines_from_last.find(|line| !line.trim_ascii().is_empty()),
+            Self::PrefixedLine(prefix) => {
+                lines_from_last.find_map(|line| line.strip_prefix(prefix.as_bytes()))
+            }
+        }
+    }
+}
+
+// file written for an evaluation, e.g. the JSON value for the params_file placeholder, removed
+// after the evaluation unless it is in the working directory
+struct EvalFile {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl EvalFile {
+    // before each attempt, so that a file left by the previous one is not read as its result
+    async fn remove(&self) {
+        tokio::fs::remove_file(&self.path).await.ok();
+    }
+}
+
+impl Drop for EvalFile {
+    fn drop(&mut self) {
+        if self.temporary {
+            std::fs::remove_file(&self.path).ok();
+        }
+    }
+}
+
+enum ChildOutcome {
+    // with the CPU time of the process, which is only measured if a CPU time limit applies
+    Exited(ExitStatus, Option<Duration>),
+    TimedOut,
+    // aborted or pruned
+    Stopped,
+}
+
+// The output is complete if the process exited, and what was captured up to the stop otherwise
+struct ChildRun {
+    outcome: ChildOutcome,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+// Applied to each objective function process and inherited by its descendants
+#[derive(Debug, Clone, Default)]
+pub struct ResourceLimits {
+    pub address_space_bytes: Option<u64>,
+    pub cpu_time_secs: Option<u64>,
+    pub open_files: Option<u64>,
+    pub core_file_size_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    fn is_empty(&self) -> bool {
+        self.address_space_bytes.is_none()
+            && self.cpu_time_secs.is_none()
+            && self.open_files.is_none()
+            && self.core_file_size_bytes.is_none()
+    }
+
+    // must only make async-signal-safe calls, it runs between fork and exec
+    fn apply(&self) -> nix::Result<()> {
+        if let Some(address_space_bytes) = self.address_space_bytes {
+            setrlimit(
+                Resource::RLIMIT_AS,
+                address_space_bytes,
+                address_space_bytes,
+            )?;
+        }
+
+        if let Some(cpu_time_secs) = self.cpu_time_secs {
+            setrlimit(
+                Resource::RLIMIT_CPU,
+                cpu_time_secs,
+                cpu_time_secs + CPU_TIME_HARD_LIMIT_MARGIN_SECS,
+            )?;
+        }
+
+        if let Some(open_files) = self.open_files {
+            setrlimit(Resource::RLIMIT_NOFILE, open_files, open_files)?;
+        }
+
+        if let Some(core_file_size_bytes) = self.core_file_size_bytes {
+            setrlimit(
+                Resource::RLIMIT_CORE,
+                core_file_size_bytes,
+                core_file_size_bytes,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Best effort: a process exceeding the CPU time limit receives a signal, one exceeding any
+    // other limit fails with an error message that is specific to the runtime it is implemented
+    // in. Crashes and kills from elsewhere are not taken for exceeded limits.
+    fn exceeded(&self, output: &Output, cpu_time: Option<Duration>) -> Option<RejectionReason> {
+        if output.status.success() {
+            return None;
+        }
+
+        let signal = output
+            .status
+            .signal()
+            .map(Signal::try_from)
+            .and_then(Result::ok);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        // SIGKILL is sent at the hard limit, to processes that do not terminate on SIGXCPU
+        let cpu_time_exceeded =
+            self.cpu_time_secs
+                .is_some_and(|cpu_time_secs| match signal {
+                    Some(Signal::SIGXCPU) => true,
+                    Some(Signal::SIGKILL) => cpu_time
+                        .is_some_and(|cpu_time| cpu_time >= Duration::from_secs(cpu_time_secs)),
+                    _ => false,
+                });
+
+        if cpu_time_exceeded {
+            Some(RejectionReason::CpuTimeLimitExceeded)
+        } else if self.open_files.is_some() && stderr.contains(TOO_MANY_OPEN_FILES_MESSAGE) {
+            Some(RejectionReason::OpenFilesLimitExceeded)
+        } else if self.address_space_bytes.is_some()
+            && OUT_OF_MEMORY_MESSAGES
+                .iter()
+                .any(|message| stderr.contains(message))
+        {
+            Some(RejectionReason::AddressSpaceLimitExceeded)
+        } else {
+            None
+        }
+    }
 }
 
 impl ObjFuncProcessDef {
@@ -30,6 +317,16 @@ impl ObjFuncProcessDef {
             program,
             args,
             kill_obj_func_after,
+            kill_signal: Signal::SIGTERM,
+            kill_grace_period: DEFAULT_KILL_GRACE_PERIOD,
+            resource_limits: ResourceLimits::default(),
+            work_dirs: None,
+            out_dir: None,
+            env: Vec::new(),
+            config_file: None,
+            result_source: ResultSource::Stdout,
+            exit_codes: ExitCodes::default(),
+            output_archive: None,
         }
     }
 }
@@ -49,65 +346,474 @@ fn kill_and_reap_child_proc_group(unreaped_pgid: Option<Pid>) -> Result<(), Erro
     }
 }
 
+// Gives the child process group the chance to clean up before it is killed
+async fn terminate_child_proc_group(
+    unreaped_pgid: Option<Pid>,
+    kill_signal: Signal,
+    grace_period: Duration,
+) -> Result<(), Error> {
+    let pgid = match unreaped_pgid {
+        Some(pgid) if kill_signal != Signal::SIGKILL => pgid,
+        _ => return kill_and_reap_child_proc_group(unreaped_pgid),
+    };
+
+    match signal::killpg(pgid, kill_signal) {
+        Err(Errno::ESRCH) => return Ok(()),
+        Err(_) => return Err(Error::FailedToKillChildProcessGroup(pgid)),
+        Ok(()) => (),
+    }
+
+    let deadline = Instant::now() + grace_period;
+
+    while Instant::now() < deadline {
+        match wait::waitpid(pgid, Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::StillAlive) => tokio::time::sleep(REAP_POLL_INTERVAL).await,
+            // the group leader is reaped, but other members of the group may still be around
+            Ok(_) => {
+                return match signal::killpg(pgid, Signal::SIGKILL) {
+                    Ok(()) | Err(Errno::ESRCH) => Ok(()),
+                    Err(_) => Err(Error::FailedToKillChildProcessGroup(pgid)),
+                }
+            }
+            Err(_) => return Err(Error::FailedToReapChildProcessGroup(pgid)),
+        }
+    }
+
+    kill_and_reap_child_proc_group(unreaped_pgid)
+}
+
 #[allow(non_snake_case)]
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 struct ObjFuncChildResult {
     objFuncVal: Option<f64>,
+    #[serde(default)]
+    constraintViolation: Option<f64>,
 }
 
-async fn get_child_result(
-    child: AsyncGroupChild,
-    obj_func_arg: &OsStr,
+impl From<ObjFuncChildResult> for ObjFuncResult {
+    fn from(child_result: ObjFuncChildResult) -> Self {
+        Self {
+            obj_func_val: child_result.objFuncVal,
+            constraint_violation: child_result.constraintViolation,
+            rejection_reason: None,
+            num_retries: 0,
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjFuncChildBatchResult {
+    id: usize,
+    objFuncVal: Option<f64>,
+    #[serde(default)]
+    constraintViolation: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct ObjFuncChildBatchItem<'a> {
+    id: usize,
     seed: u64,
-    individual_id: usize,
-) -> Result<Option<f64>, Error> {
-    let output = child.wait_with_output().await?;
+    value: &'a serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    budget: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IntermediateReport {
+    step: usize,
+    intermediate: f64,
+}
+
+// the output is shared, so that it is kept if reading is given up
+async fn read_stderr(mut stderr_pipe: ChildStderr, stderr: Arc<Mutex<Vec<u8>>>) -> io::Result<()> {
+    let mut buf = [0; 4096];
+
+    loop {
+        match stderr_pipe.read(&mut buf).await? {
+            0 => return Ok(()),
+            num_bytes => stderr.lock().unwrap().extend_from_slice(&buf[..num_bytes]),
+        }
+    }
+}
+
+// stdout is collected in the given buffer, so that it is kept if the future is dropped
+async fn get_child_output(
+    mut child: AsyncGroupChild,
+    stdout: &mut Vec<u8>,
+    unreaped_pgid: Option<Pid>,
+    intermediate_reporter: &IntermediateReporter,
+    process_def: &ObjFuncProcessDef,
+) -> Result<Option<(ExitStatus, Option<Duration>)>, Error> {
+    let mut stdout_reader = BufReader::new(child.inner().stdout.take().unwrap());
+    let mut line = Vec::new();
+
+    while stdout_reader.read_until(b'\n', &mut line).await? > 0 {
+        // intermediate reports are consumed here, all other output is kept for the final result
+        match serde_json::from_slice::<IntermediateReport>(&line) {
+            Ok(report) => {
+                if intermediate_reporter.report(report.step, report.intermediate) {
+                    terminate_child_proc_group(
+                        unreaped_pgid,
+                        process_def.kill_signal,
+                        process_def.kill_grace_period,
+                    )
+                    .await?;
+                    return Ok(None);
+                }
+            }
+            Err(_) => stdout.extend_from_slice(&line),
+        }
+
+        line.clear();
+    }
+
+    // must be measured before the process is reaped
+    let cpu_time = match (unreaped_pgid, process_def.resource_limits.cpu_time_secs) {
+        (Some(pid), Some(_)) => tokio::task::spawn_blocking(move || exited_cpu_time(pid))
+            .await
+            .unwrap(),
+        _ => None,
+    };
+
+    let status = child.wait().await?;
+
+    Ok(Some((status, cpu_time)))
+}
+
+// Waits for the process to exit without reaping it, so that its CPU time can still be read
+fn exited_cpu_time(pid: Pid) -> Option<Duration> {
+    let mut info = MaybeUninit::<nix::libc::siginfo_t>::zeroed();
+
+    // SAFETY: waitid only writes to the siginfo_t it is given
+    let ret = unsafe {
+        nix::libc::waitid(
+            nix::libc::P_PID,
+            pid.as_raw() as nix::libc::id_t,
+            info.as_mut_ptr(),
+            nix::libc::WEXITED | nix::libc::WNOWAIT,
+        )
+    };
 
+    if ret != 0 {
+        return None;
+    }
+
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    // the fields following the command name, which is in parentheses and may contain spaces,
+    // start with the state; utime and stime follow at offsets 11 and 12
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let ticks_per_sec = sysconf(SysconfVar::CLK_TCK).ok()??;
+
+    Some(Duration::from_secs_f64(
+        (utime + stime) as f64 / ticks_per_sec as f64,
+    ))
+}
+
+fn parse_child_output<T: DeserializeOwned>(
+    output: Output,
+    result: Option<&[u8]>,
+    exit_codes: &ExitCodes,
+    retries_left: bool,
+    obj_func_arg: &OsStr,
+    seed: Option<u64>,
+    individual_descr: &str,
+) -> Result<ChildResult<T>, Error> {
     if !output.stderr.is_empty() {
         info!(
-            "Individual {}: non-empty stderr: {}",
-            individual_id,
+            "{}: non-empty stderr: {}",
+            individual_descr,
             String::from_utf8(output.stderr.clone()).unwrap()
         );
     }
 
-    if output.status.success() {
-        let result: ObjFuncChildResult = serde_json::from_slice(&output.stdout).map_err(|_| {
-            Error::ObjFuncProcInvalidOutput(ProcOutputWithObjFuncArg::new(
+    match exit_codes.outcome(output.status) {
+        Some(ExitCodeOutcome::Parse) => result
+            .and_then(|result| serde_json::from_slice(result).ok())
+            .map(ChildResult::Parsed)
+            .ok_or_else(|| {
+                Error::ObjFuncProcInvalidOutput(ProcOutputWithObjFuncArg::new(
+                    obj_func_arg.to_owned(),
+                    seed,
+                    output,
+                ))
+            }),
+        Some(ExitCodeOutcome::Reject) => {
+            info!("{}: rejected with {}", individual_descr, output.status);
+            Ok(ChildResult::Rejected)
+        }
+        Some(ExitCodeOutcome::Retry) if retries_left => {
+            info!("{}: retrying after {}", individual_descr, output.status);
+            Ok(ChildResult::Retry)
+        }
+        _ => {
+            trace!(
+                "Child terminated unsuccessfully, status: {:?}",
+                output.status
+            );
+            Err(Error::ObjFuncProcFailed(ProcOutputWithObjFuncArg::new(
                 obj_func_arg.to_owned(),
                 seed,
                 output,
-            ))
-        })?;
-        Ok(result.objFuncVal)
-    } else {
-        trace!(
-            "Child terminated unsuccessfully, status: {:?}",
-            output.status
-        );
-        Err(Error::ObjFuncProcFailed(ProcOutputWithObjFuncArg::new(
-            obj_func_arg.to_owned(),
-            seed,
-            output,
-        )))
+            )))
+        }
     }
 }
 
-#[async_trait]
-impl AsyncObjectiveFunction for ObjFuncProcessDef {
-    async fn evaluate(
+impl ObjFuncProcessDef {
+    fn make_command(&self) -> Result<Command, Error> {
+        let program = Path::new(&self.program);
+
+        // the child runs in its working directory, so relative paths to the program must not be
+        // resolved against it
+        if self.work_dirs.is_some() && program.is_relative() && program.components().count() > 1 {
+            Ok(Command::new(path::absolute(program)?))
+        } else {
+            Ok(Command::new(program))
+        }
+    }
+
+    // placeholders in the arguments are substituted if any are used, otherwise the JSON value
+    // and further positional arguments are appended
+    fn add_args(
         &self,
-        value: serde_json::Value,
+        command: &mut Command,
+        arg_ctx: &ArgContext,
+        positional_args: Vec<OsString>,
+    ) -> Result<(), Error> {
+        if arg_template::has_placeholders(&self.args) {
+            for arg in &self.args {
+                command.arg(arg_template::substitute(arg, arg_ctx)?);
+            }
+        } else {
+            command.args(&self.args).args(positional_args);
+        }
+
+        Ok(())
+    }
+
+    // environment and working directory common to individual and batch evaluations
+    fn set_env(
+        &self,
+        command: &mut Command,
+        slot: usize,
+        work_dir: Option<&Path>,
+        result_file: Option<&Path>,
+    ) {
+        command
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .env(SLOT_ENV_VAR, slot.to_string());
+
+        if let Some(work_dir) = work_dir {
+            command
+                .current_dir(work_dir)
+                .env(WORK_DIR_ENV_VAR, work_dir);
+        }
+
+        if let Some(out_dir) = &self.out_dir {
+            command.env(OUT_DIR_ENV_VAR, out_dir);
+        }
+
+        if let Some(result_file) = result_file {
+            command.env(RESULT_FILE_ENV_VAR, result_file);
+        }
+
+        if let Some(kill_obj_func_after) = self.kill_obj_func_after {
+            let deadline = (SystemTime::now() + kill_obj_func_after)
+                .duration_since(UNIX_EPOCH)
+                .unwrap();
+
+            command.env(DEADLINE_ENV_VAR, format!("{:.3}", deadline.as_secs_f64()));
+        }
+    }
+
+    async fn create_work_dir(&self, name: &str) -> Result<Option<PathBuf>, Error> {
+        match &self.work_dirs {
+            Some(work_dirs) => Ok(Some(work_dirs.create(name).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn create_log_dir(&self, name: &str) -> Result<Option<PathBuf>, Error> {
+        match &self.output_archive {
+            Some(output_archive) => Ok(Some(output_archive.create(name).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn archive_output(
+        &self,
+        log_dir: Option<&Path>,
+        child_run: &ChildRun,
+    ) -> Result<(), Error> {
+        match (&self.output_archive, log_dir) {
+            (Some(output_archive), Some(log_dir)) => {
+                output_archive
+                    .write(log_dir, &child_run.stdout, &child_run.stderr)
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn eval_file(&self, work_dir: Option<&Path>, eval_name: &str, file_name: &str) -> EvalFile {
+        match work_dir {
+            Some(work_dir) => EvalFile {
+                path: work_dir.join(file_name),
+                temporary: false,
+            },
+            None => EvalFile {
+                path: std::env::temp_dir().join(format!(
+                    "cambrian_{}_{}_{}",
+                    std::process::id(),
+                    eval_name,
+                    file_name
+                )),
+                temporary: true,
+            },
+        }
+    }
+
+    async fn write_eval_file(
+        &self,
+        work_dir: Option<&Path>,
+        eval_name: &str,
+        file_name: &str,
+        contents: &str,
+    ) -> Result<EvalFile, Error> {
+        let eval_file = self.eval_file(work_dir, eval_name, file_name);
+        tokio::fs::write(&eval_file.path, contents).await?;
+
+        Ok(eval_file)
+    }
+
+    // written by the process, so only its path is determined here
+    fn result_file(&self, work_dir: Option<&Path>, eval_name: &str) -> Option<EvalFile> {
+        (self.result_source == ResultSource::File)
+            .then(|| self.eval_file(work_dir, eval_name, RESULT_FILE_NAME))
+    }
+
+    async fn extract_result(
+        &self,
+        output: &Output,
+        result_file: Option<&EvalFile>,
+    ) -> Option<Vec<u8>> {
+        match result_file {
+            Some(result_file) => tokio::fs::read(&result_file.path).await.ok(),
+            None => self
+                .result_source
+                .extract(&output.stdout)
+                .map(<[u8]>::to_vec),
+        }
+    }
+
+    async fn write_params_file(
+        &self,
+        work_dir: Option<&Path>,
+        eval_name: &str,
+        json_str: &str,
+    ) -> Result<Option<EvalFile>, Error> {
+        if !arg_template::uses_params_file(&self.args) {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.write_eval_file(work_dir, eval_name, "params.json", json_str)
+                .await?,
+        ))
+    }
+
+    // rendered with the same values as the arguments, except for the config file itself
+    async fn write_config_file(
+        &self,
+        work_dir: Option<&Path>,
+        eval_name: &str,
+        arg_ctx: &ArgContext<'_>,
+    ) -> Result<Option<EvalFile>, Error> {
+        match &self.config_file {
+            Some(config_file) => {
+                let contents = config_file.render(arg_ctx)?;
+                Ok(Some(
+                    self.write_eval_file(work_dir, eval_name, &config_file.file_name, &contents)
+                        .await?,
+                ))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // directories of evaluations failing with an error are not touched at all
+    async fn retain_dirs(
+        &self,
+        work_dir: Option<PathBuf>,
+        log_dir: Option<PathBuf>,
+        stopped: bool,
+        results: &[ObjFuncResult],
+    ) -> Result<(), Error> {
+        if let (Some(work_dirs), Some(work_dir)) = (&self.work_dirs, work_dir) {
+            work_dirs.retain(work_dir, stopped, results).await?;
+        }
+
+        if let (Some(output_archive), Some(log_dir)) = (&self.output_archive, log_dir) {
+            output_archive
+                .log_dirs()
+                .retain(log_dir, stopped, results)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // all items of a batch are rejected together
+    async fn reject_batch(
+        &self,
+        work_dir: Option<PathBuf>,
+        log_dir: Option<PathBuf>,
+        stopped: bool,
+        result: ObjFuncResult,
+        num_items: usize,
+    ) -> Result<Vec<ObjFuncResult>, Error> {
+        let results = vec![result; num_items];
+        self.retain_dirs(work_dir, log_dir, stopped, &results)
+            .await?;
+
+        Ok(results)
+    }
+
+    async fn run_child(
+        &self,
+        mut command: Command,
+        cpu_set: Option<&[usize]>,
         mut abort_sig_rx: async_broadcast::Receiver<()>,
-        seed: u64,
-        individual_id: usize,
-    ) -> Result<Option<f64>, Error> {
-        let json_arg: OsString = serde_json::to_string(&value).unwrap().into();
-        let child = Command::new(&self.program)
-            .args(&self.args)
-            .arg(&json_arg)
-            .arg(seed.to_string())
+        intermediate_reporter: &IntermediateReporter,
+    ) -> Result<ChildRun, Error> {
+        if !self.resource_limits.is_empty() {
+            let resource_limits = self.resource_limits.clone();
+
+            // SAFETY: setrlimit is async-signal-safe
+            unsafe {
+                command.pre_exec(move || resource_limits.apply().map_err(io::Error::from));
+            }
+        }
+
+        if let Some(cpu_set) = cpu_set {
+            let cpu_set = affinity::to_cpu_set(cpu_set)?;
+
+            // SAFETY: sched_setaffinity is a plain system call
+            unsafe {
+                command.pre_exec(move || {
+                    sched_setaffinity(Pid::from_raw(0), &cpu_set).map_err(io::Error::from)
+                });
+            }
+        }
+
+        let mut child = command
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .group_spawn()
@@ -115,31 +821,444 @@ impl AsyncObjectiveFunction for ObjFuncProcessDef {
 
         let unreaped_pgid = child.id().map(|pgid| Pid::from_raw(pgid as i32));
 
-        let child_result = get_child_result(child, &json_arg, seed, individual_id);
+        let stderr = Arc::new(Mutex::new(Vec::new()));
+        let mut stderr_task = tokio::spawn(read_stderr(
+            child.inner().stderr.take().unwrap(),
+            stderr.clone(),
+        ));
+        let mut stdout = Vec::new();
 
-        let mut timeout_fut = if let Some(kill_after_duration) = self.kill_obj_func_after {
-            let timeout_fut = Box::pin(tokio::time::sleep(kill_after_duration));
-            Either::Left(timeout_fut)
-        } else {
-            Either::Right(futures::future::pending())
+        let outcome = {
+            let child_output = get_child_output(
+                child,
+                &mut stdout,
+                unreaped_pgid,
+                intermediate_reporter,
+                self,
+            );
+
+            let mut timeout_fut = if let Some(kill_after_duration) = self.kill_obj_func_after {
+                let timeout_fut = Box::pin(tokio::time::sleep(kill_after_duration));
+                Either::Left(timeout_fut)
+            } else {
+                Either::Right(futures::future::pending())
+            };
+
+            tokio::pin!(child_output);
+
+            let abort_sig_future = abort_sig_rx.recv();
+
+            tokio::select! {
+                result = &mut child_output => {
+                    result?.map_or(ChildOutcome::Stopped, |(status, cpu_time)| ChildOutcome::Exited(status, cpu_time))
+                }
+                _ = &mut timeout_fut => {
+                    terminate_child_proc_group(unreaped_pgid, self.kill_signal, self.kill_grace_period).await?;
+                    ChildOutcome::TimedOut
+                }
+                _ = abort_sig_future => {
+                    terminate_child_proc_group(unreaped_pgid, self.kill_signal, self.kill_grace_period).await?;
+                    ChildOutcome::Stopped
+                }
+            }
         };
 
-        tokio::pin!(child_result);
+        if matches!(outcome, ChildOutcome::Exited(..)) {
+            (&mut stderr_task).await.unwrap()?;
+        } else if tokio::time::timeout(STDERR_DRAIN_TIMEOUT, &mut stderr_task)
+            .await
+            .is_err()
+        {
+            // descendants that left the process group may keep the pipe open
+            stderr_task.abort();
+        }
+
+        let stderr = std::mem::take(&mut *stderr.lock().unwrap());
 
-        let abort_sig_future = abort_sig_rx.recv();
+        Ok(ChildRun {
+            outcome,
+            stdout,
+            stderr,
+        })
+    }
+}
 
-        tokio::select! {
-            result = &mut child_result => {
-                return result
+#[async_trait]
+impl AsyncObjectiveFunction for ObjFuncProcessDef {
+    async fn evaluate(
+        &self,
+        value: serde_json::Value,
+        abort_sig_rx: async_broadcast::Receiver<()>,
+        eval_ctx: &EvalContext,
+        intermediate_reporter: &IntermediateReporter,
+    ) -> Result<ObjFuncResult, Error> {
+        let seed = eval_ctx.seed;
+        let json_str = serde_json::to_string(&value).unwrap();
+        let json_arg: OsString = json_str.clone().into();
+        let eval_name = format!("{}-{}", eval_ctx.individual_id, seed);
+
+        let work_dir = self.create_work_dir(&eval_name).await?;
+        let log_dir = self.create_log_dir(&eval_name).await?;
+        let params_file = self
+            .write_params_file(work_dir.as_deref(), &eval_name, &json_str)
+            .await?;
+        let result_file = self.result_file(work_dir.as_deref(), &eval_name);
+
+        let arg_ctx = ArgContext {
+            params: &value,
+            params_file: params_file
+                .as_ref()
+                .map(|params_file| params_file.path.as_path()),
+            seed: Some(seed),
+            budget: eval_ctx.budget,
+            individual_id: Some(eval_ctx.individual_id),
+            work_dir: work_dir.as_deref(),
+            config_file: None,
+            result_file: result_file
+                .as_ref()
+                .map(|result_file| result_file.path.as_path()),
+        };
+
+        let config_file = self
+            .write_config_file(work_dir.as_deref(), &eval_name, &arg_ctx)
+            .await?;
+        let arg_ctx = ArgContext {
+            config_file: config_file
+                .as_ref()
+                .map(|config_file| config_file.path.as_path()),
+            ..arg_ctx
+        };
+
+        let mut positional_args = vec![json_arg.clone(), seed.to_string().into()];
+
+        if let Some(budget) = eval_ctx.budget {
+            positional_args.push(budget.to_string().into());
+        }
+
+        let individual_descr = format!("Individual {}", eval_ctx.individual_id);
+        let mut num_retries = 0;
+
+        let (mut result, stopped) = loop {
+            if let Some(result_file) = &result_file {
+                result_file.remove().await;
             }
-            _ = &mut timeout_fut => {
-                kill_and_reap_child_proc_group(unreaped_pgid)?;
-                return Ok(None)
+
+            let mut command = self.make_command()?;
+            self.add_args(&mut command, &arg_ctx, positional_args.clone())?;
+            self.set_env(
+                &mut command,
+                eval_ctx.slot,
+                work_dir.as_deref(),
+                arg_ctx.result_file,
+            );
+            command
+                .env(INDIVIDUAL_ID_ENV_VAR, eval_ctx.individual_id.to_string())
+                .env(SEED_ENV_VAR, seed.to_string());
+
+            if let Some(config_file) = &config_file {
+                command.env(CONFIG_FILE_ENV_VAR, &config_file.path);
+            }
+
+            if let Some(sample_index) = eval_ctx.sample_index {
+                command.env(SAMPLE_INDEX_ENV_VAR, sample_index.to_string());
+            }
+
+            let child_run = self
+                .run_child(
+                    command,
+                    eval_ctx.cpu_set.as_deref(),
+                    abort_sig_rx.clone(),
+                    intermediate_reporter,
+                )
+                .await?;
+
+            self.archive_output(log_dir.as_deref(), &child_run).await?;
+
+            let (output, cpu_time) = match child_run.outcome {
+                ChildOutcome::Exited(status, cpu_time) => (
+                    Output {
+                        status,
+                        stdout: child_run.stdout,
+                        stderr: child_run.stderr,
+                    },
+                    cpu_time,
+                ),
+                ChildOutcome::TimedOut => {
+                    break (
+                        ObjFuncResult::rejected_with_reason(RejectionReason::Timeout),
+                        false,
+                    )
+                }
+                ChildOutcome::Stopped => break (ObjFuncResult::rejected(), true),
+            };
+
+            if let Some(rejection_reason) = self.resource_limits.exceeded(&output, cpu_time) {
+                info!("{}: rejected: {}", individual_descr, rejection_reason);
+                break (ObjFuncResult::rejected_with_reason(rejection_reason), false);
             }
-            _ = abort_sig_future => {
-                kill_and_reap_child_proc_group(unreaped_pgid)?;
-                return Ok(None)
+
+            let result = self.extract_result(&output, result_file.as_ref()).await;
+
+            match parse_child_output::<ObjFuncChildResult>(
+                output,
+                result.as_deref(),
+                &self.exit_codes,
+                num_retries < self.exit_codes.max_retries,
+                &json_arg,
+                Some(seed),
+                &individual_descr,
+            )? {
+                ChildResult::Parsed(child_result) => break (child_result.into(), false),
+                ChildResult::Rejected => {
+                    break (
+                        ObjFuncResult::rejected_with_reason(RejectionReason::ExitCode),
+                        false,
+                    )
+                }
+                ChildResult::Retry => num_retries += 1,
             }
+        };
+
+        result.num_retries = num_retries;
+        self.retain_dirs(work_dir, log_dir, stopped, &[result])
+            .await?;
+
+        Ok(result)
+    }
+
+    async fn evaluate_batch(
+        &self,
+        batch_items: Vec<BatchItem>,
+        abort_sig_rx: async_broadcast::Receiver<()>,
+    ) -> Result<Vec<ObjFuncResult>, Error> {
+        let child_batch_items = batch_items
+            .iter()
+            .map(|batch_item| ObjFuncChildBatchItem {
+                id: batch_item.eval_ctx.individual_id,
+                seed: batch_item.eval_ctx.seed,
+                value: &batch_item.value,
+                budget: batch_item.eval_ctx.budget,
+            })
+            .collect_vec();
+
+        let json_value = serde_json::to_value(&child_batch_items).unwrap();
+        let json_str = json_value.to_string();
+        let json_arg: OsString = json_str.clone().into();
+        let eval_name = batch_items
+            .first()
+            .map(|batch_item| format!("batch-{}", batch_item.eval_ctx.seed))
+            .unwrap_or_default();
+
+        let work_dir = self.create_work_dir(&eval_name).await?;
+        let log_dir = self.create_log_dir(&eval_name).await?;
+        let params_file = self
+            .write_params_file(work_dir.as_deref(), &eval_name, &json_str)
+            .await?;
+        let result_file = self.result_file(work_dir.as_deref(), &eval_name);
+
+        let arg_ctx = ArgContext {
+            params: &json_value,
+            params_file: params_file
+                .as_ref()
+                .map(|params_file| params_file.path.as_path()),
+            seed: None,
+            budget: None,
+            individual_id: None,
+            work_dir: work_dir.as_deref(),
+            // config files are rejected in batch mode before the launch
+            config_file: None,
+            result_file: result_file
+                .as_ref()
+                .map(|result_file| result_file.path.as_path()),
+        };
+
+        // all items of a batch share the slot of the batch
+        let cpu_set = batch_items
+            .first()
+            .and_then(|batch_item| batch_item.eval_ctx.cpu_set.as_deref());
+
+        let individual_descr = format!(
+            "Individuals {:?}",
+            batch_items
+                .iter()
+                .map(|batch_item| batch_item.eval_ctx.individual_id)
+                .collect_vec()
+        );
+
+        let mut num_retries = 0;
+
+        let (output, results) = loop {
+            if let Some(result_file) = &result_file {
+                result_file.remove().await;
+            }
+
+            let mut command = self.make_command()?;
+            self.add_args(&mut command, &arg_ctx, vec![json_arg.clone()])?;
+
+            if let Some(batch_item) = batch_items.first() {
+                self.set_env(
+                    &mut command,
+                    batch_item.eval_ctx.slot,
+                    work_dir.as_deref(),
+                    arg_ctx.result_file,
+                );
+            }
+
+            let child_run = self
+                .run_child(
+                    command,
+                    cpu_set,
+                    abort_sig_rx.clone(),
+                    &IntermediateReporter::disabled(),
+                )
+                .await?;
+
+            self.archive_output(log_dir.as_deref(), &child_run).await?;
+
+            let (output, cpu_time) = match child_run.outcome {
+                ChildOutcome::Exited(status, cpu_time) => (
+                    Output {
+                        status,
+                        stdout: child_run.stdout,
+                        stderr: child_run.stderr,
+                    },
+                    cpu_time,
+                ),
+                outcome => {
+                    let stopped = matches!(outcome, ChildOutcome::Stopped);
+                    let rejected = if stopped {
+                        ObjFuncResult::rejected()
+                    } else {
+                        ObjFuncResult::rejected_with_reason(RejectionReason::Timeout)
+                    };
+                    let result = ObjFuncResult {
+                        num_retries,
+                        ..rejected
+                    };
+                    return self
+                        .reject_batch(work_dir, log_dir, stopped, result, batch_items.len())
+                        .await;
+                }
+            };
+
+            if let Some(rejection_reason) = self.resource_limits.exceeded(&output, cpu_time) {
+                info!("{}: rejected: {}", individual_descr, rejection_reason);
+                let result = ObjFuncResult {
+                    num_retries,
+                    ..ObjFuncResult::rejected_with_reason(rejection_reason)
+                };
+                return self
+                    .reject_batch(work_dir, log_dir, false, result, batch_items.len())
+                    .await;
+            }
+
+            let result = self.extract_result(&output, result_file.as_ref()).await;
+
+            match parse_child_output::<Vec<ObjFuncChildBatchResult>>(
+                output.clone(),
+                result.as_deref(),
+                &self.exit_codes,
+                num_retries < self.exit_codes.max_retries,
+                &json_arg,
+                None,
+                &individual_descr,
+            )? {
+                ChildResult::Parsed(results) => break (output, results),
+                ChildResult::Rejected => {
+                    let result = ObjFuncResult {
+                        num_retries,
+                        ..ObjFuncResult::rejected_with_reason(RejectionReason::ExitCode)
+                    };
+                    return self
+                        .reject_batch(work_dir, log_dir, false, result, batch_items.len())
+                        .await;
+                }
+                ChildResult::Retry => num_retries += 1,
+            }
+        };
+
+        let invalid_output = |output| {
+            Error::ObjFuncProcInvalidOutput(ProcOutputWithObjFuncArg::new(
+                json_arg.clone(),
+                None,
+                output,
+            ))
+        };
+
+        let mut results_by_id: HashMap<usize, ObjFuncResult> = results
+            .into_iter()
+            .map(|result| {
+                (
+                    result.id,
+                    ObjFuncResult {
+                        obj_func_val: result.objFuncVal,
+                        constraint_violation: result.constraintViolation,
+                        rejection_reason: None,
+                        num_retries,
+                    },
+                )
+            })
+            .collect();
+
+        if results_by_id.len() != batch_items.len() {
+            return Err(invalid_output(output));
+        }
+
+        let results = batch_items
+            .iter()
+            .map(|batch_item| results_by_id.remove(&batch_item.eval_ctx.individual_id))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid_output(output))?;
+
+        self.retain_dirs(work_dir, log_dir, false, &results).await?;
+
+        Ok(results)
+    }
+
+    fn validate(&self, algo_config: &AlgoConfig) -> Result<(), Error> {
+        self.exit_codes.validate()?;
+
+        let batch_mode = algo_config.batch_size.is_some();
+
+        // one config file per individual would not fit a single process evaluating a batch
+        if batch_mode && self.config_file.is_some() {
+            return Err(Error::ConfigFileInBatchMode);
+        }
+
+        let available = AvailablePlaceholders {
+            seed: !batch_mode,
+            budget: !batch_mode && algo_config.fidelity.is_some(),
+            individual_id: !batch_mode,
+            work_dir: self.work_dirs.is_some(),
+            config_file: self.config_file.is_some(),
+            result_file: self.result_source == ResultSource::File,
+        };
+
+        let args = self.args.iter().filter_map(|arg| arg.to_str());
+        let mut unavailable = arg_template::unavailable_placeholders(args, &available);
+
+        // the config file is rendered before its own path is known
+        if let Some(ConfigFileDef {
+            template: ConfigTemplate::Text(text),
+            ..
+        }) = &self.config_file
+        {
+            let available = AvailablePlaceholders {
+                config_file: false,
+                ..available
+            };
+            unavailable.extend(arg_template::unavailable_placeholders(
+                [text.as_str()],
+                &available,
+            ));
+        }
+
+        if unavailable.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnavailablePlaceholders(
+                unavailable.into_iter().unique().collect(),
+            ))
         }
     }
 }
@@ -0,0 +1,162 @@
+use crate::meta::PruningConfig;
+use crate::types::HashMap;
+use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Compares intermediate objective function values against all values reported by other
+// evaluations at the same step. An evaluation is pruned if its value is worse than the
+// configured quantile of those, given enough reports are available at that step.
+pub(crate) struct Pruner {
+    maximize: bool,
+    quantile: f64,
+    min_num_reports: usize,
+    reports_by_step: HashMap<usize, Vec<f64>>,
+}
+
+impl Pruner {
+    pub fn new(pruning_config: &PruningConfig, maximize: bool) -> Self {
+        Self {
+            maximize,
+            quantile: pruning_config.quantile,
+            min_num_reports: pruning_config.min_num_reports,
+            reports_by_step: HashMap::default(),
+        }
+    }
+
+    pub fn should_prune(&mut self, step: usize, intermediate: f64) -> bool {
+        if !intermediate.is_finite() {
+            return false;
+        }
+
+        let intermediate = if self.maximize {
+            -intermediate
+        } else {
+            intermediate
+        };
+
+        let reports = self.reports_by_step.entry(step).or_default();
+
+        let result = reports.len() >= self.min_num_reports && {
+            let quantile_idx = ((reports.len() - 1) as f64 * self.quantile).round() as usize;
+            intermediate > reports[quantile_idx]
+        };
+
+        let insert_idx = reports.partition_point(|report| *report <= intermediate);
+        reports.insert(insert_idx, intermediate);
+
+        result
+    }
+}
+
+pub struct IntermediateReporter {
+    pruner: Option<Arc<Mutex<Pruner>>>,
+    individual_id: usize,
+    pruned: AtomicBool,
+}
+
+impl IntermediateReporter {
+    pub(crate) fn new(pruner: Option<Arc<Mutex<Pruner>>>, individual_id: usize) -> Self {
+        Self {
+            pruner,
+            individual_id,
+            pruned: AtomicBool::new(false),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(None, 0)
+    }
+
+    // returns true if the evaluation is to be aborted
+    pub fn report(&self, step: usize, intermediate: f64) -> bool {
+        let should_prune = self
+            .pruner
+            .as_ref()
+            .map(|pruner| pruner.lock().unwrap().should_prune(step, intermediate))
+            .unwrap_or(false);
+
+        if should_prune {
+            info!(
+                "Individual {}: pruned at step {} with intermediate value {}",
+                self.individual_id, step, intermediate
+            );
+            self.pruned.store(true, Ordering::SeqCst);
+        }
+
+        should_prune
+    }
+
+    pub fn is_pruned(&self) -> bool {
+        self.pruned.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_pruner(quantile: f64, min_num_reports: usize) -> Arc<Mutex<Pruner>> {
+        Arc::new(Mutex::new(Pruner::new(
+            &PruningConfig {
+                quantile,
+                min_num_reports,
+            },
+            false,
+        )))
+    }
+
+    #[test]
+    fn prune_worse_than_median() {
+        let pruner = make_pruner(0.5, 3);
+        let mut sut = pruner.lock().unwrap();
+
+        for intermediate in [3.0, 1.0, 2.0] {
+            assert!(!sut.should_prune(0, intermediate));
+        }
+
+        assert!(!sut.should_prune(0, 2.0));
+        assert!(sut.should_prune(0, 2.5));
+
+        // other steps are unaffected
+        assert!(!sut.should_prune(1, 10.0));
+    }
+
+    #[test]
+    fn prune_worse_than_median_when_maximizing() {
+        let pruner = make_pruner(0.5, 1);
+        let mut sut = pruner.lock().unwrap();
+        sut.maximize = true;
+
+        assert!(!sut.should_prune(0, 0.8));
+        assert!(sut.should_prune(0, 0.7));
+        assert!(!sut.should_prune(0, 0.9));
+    }
+
+    #[test]
+    fn reporter() {
+        let pruner = make_pruner(0.0, 1);
+        let first = IntermediateReporter::new(Some(pruner.clone()), 0);
+        let second = IntermediateReporter::new(Some(pruner), 1);
+
+        assert!(!first.report(0, 1.0));
+        assert!(!second.report(0, 0.5));
+        assert!(!second.is_pruned());
+
+        assert!(!first.report(1, 1.0));
+        assert!(!first.is_pruned());
+        assert!(first.report(0, 1.0));
+        assert!(first.is_pruned());
+    }
+
+    #[test]
+    fn disabled_reporter() {
+        let sut = IntermediateReporter::disabled();
+
+        for step in 0..10 {
+            assert!(!sut.report(step, step as f64));
+        }
+
+        assert!(!sut.is_pruned());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::termination::TerminationCriterion;
 use std::{fmt::Display, time::Duration};
 
 #[derive(Debug)]
@@ -5,16 +6,33 @@ pub struct FinalReport {
     pub best_seen: BestSeen,
     pub num_obj_func_eval_completed: usize,
     pub num_obj_func_eval_rejected: usize,
+    pub num_obj_func_eval_pruned: usize,
+    pub num_obj_func_eval_infeasible: usize,
+    pub num_obj_func_eval_limit_exceeded: usize,
+    pub num_obj_func_eval_rejected_by_exit_code: usize,
+    pub num_obj_func_eval_retries: usize,
+    pub num_eval_cache_hits: usize,
+    pub num_eval_cache_misses: usize,
     pub processing_time: Duration,
+    pub termination_reason: Option<TerminationCriterion>,
 }
 
 impl FinalReport {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         obj_func_val: f64,
         value: serde_json::Value,
         num_obj_func_eval_completed: usize,
         num_obj_func_eval_rejected: usize,
+        num_obj_func_eval_pruned: usize,
+        num_obj_func_eval_infeasible: usize,
+        num_obj_func_eval_limit_exceeded: usize,
+        num_obj_func_eval_rejected_by_exit_code: usize,
+        num_obj_func_eval_retries: usize,
+        num_eval_cache_hits: usize,
+        num_eval_cache_misses: usize,
         processing_time: Duration,
+        termination_reason: Option<TerminationCriterion>,
     ) -> Self {
         Self {
             best_seen: BestSeen {
@@ -23,7 +41,15 @@ impl FinalReport {
             },
             num_obj_func_eval_completed,
             num_obj_func_eval_rejected,
+            num_obj_func_eval_pruned,
+            num_obj_func_eval_infeasible,
+            num_obj_func_eval_limit_exceeded,
+            num_obj_func_eval_rejected_by_exit_code,
+            num_obj_func_eval_retries,
+            num_eval_cache_hits,
+            num_eval_cache_misses,
             processing_time,
+            termination_reason,
         }
     }
 }
@@ -41,12 +67,30 @@ impl Display for FinalReport {
             "Best seen objective function value: {}
 Number of completed objective function evaluations: {}
 Number of rejected objective function evaluations: {}
+Number of pruned objective function evaluations: {}
+Number of infeasible objective function evaluations: {}
+Number of objective function evaluations exceeding resource limits: {}
+Number of objective function evaluations rejected by exit code: {}
+Number of objective function evaluation retries: {}
+Number of evaluation cache hits: {}
+Number of evaluation cache misses: {}
 Processing time: {} seconds
+Termination reason: {}
         ",
             self.best_seen.obj_func_val,
             self.num_obj_func_eval_completed,
             self.num_obj_func_eval_rejected,
-            self.processing_time.as_secs_f64()
+            self.num_obj_func_eval_pruned,
+            self.num_obj_func_eval_infeasible,
+            self.num_obj_func_eval_limit_exceeded,
+            self.num_obj_func_eval_rejected_by_exit_code,
+            self.num_obj_func_eval_retries,
+            self.num_eval_cache_hits,
+            self.num_eval_cache_misses,
+            self.processing_time.as_secs_f64(),
+            self.termination_reason
+                .as_ref()
+                .map_or_else(|| "none".to_string(), ToString::to_string)
         )
     }
 }
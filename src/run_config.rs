@@ -0,0 +1,109 @@
+use crate::error::Error;
+use crate::meta::ObjectiveConfig;
+use crate::process::ExitCodes;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct RunConfig {
+    pub objective: ObjectiveConfig,
+    // extra environment variables for the objective function processes
+    pub env: BTreeMap<String, String>,
+    pub exit_codes: ExitCodes,
+}
+
+pub fn from_yaml_str(yaml_str: &str) -> Result<RunConfig, Error> {
+    let run_config: RunConfig = serde_yaml::from_str(yaml_str)?;
+    run_config.exit_codes.validate()?;
+
+    Ok(run_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::ObjectiveTransform;
+
+    #[test]
+    fn objective_section() {
+        let run_config = from_yaml_str(
+            "
+objective:
+    maximize: true
+    transform: log
+",
+        )
+        .unwrap();
+
+        assert!(run_config.objective.maximize);
+        assert_eq!(run_config.objective.transform, ObjectiveTransform::Log);
+    }
+
+    #[test]
+    fn objective_section_defaults() {
+        let run_config = from_yaml_str("objective: {}").unwrap();
+
+        assert!(!run_config.objective.maximize);
+        assert_eq!(run_config.objective.transform, ObjectiveTransform::Identity);
+    }
+
+    #[test]
+    fn env_section() {
+        let run_config = from_yaml_str(
+            "
+env:
+    OMP_NUM_THREADS: '1'
+    DATA_DIR: /data
+",
+        )
+        .unwrap();
+
+        assert_eq!(run_config.env["OMP_NUM_THREADS"], "1");
+        assert_eq!(run_config.env["DATA_DIR"], "/data");
+    }
+
+    #[test]
+    fn exit_codes_section() {
+        let run_config = from_yaml_str(
+            "
+exitCodes:
+    reject: [3]
+    retry: [75]
+    maxRetries: 5
+",
+        )
+        .unwrap();
+
+        assert_eq!(
+            run_config.exit_codes,
+            ExitCodes {
+                parse: vec![0],
+                reject: vec![3],
+                retry: vec![75],
+                max_retries: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn overlapping_exit_codes() {
+        assert!(matches!(
+            from_yaml_str("exitCodes:\n    reject: [3, 75]\n    retry: [75]"),
+            Err(Error::OverlappingExitCodes(75))
+        ));
+        assert!(matches!(
+            from_yaml_str("exitCodes:\n    reject: [0]"),
+            Err(Error::OverlappingExitCodes(0))
+        ));
+        assert!(from_yaml_str("exitCodes:\n    reject: [3, 3]").is_ok());
+    }
+
+    #[test]
+    fn unknown_field() {
+        assert!(matches!(
+            from_yaml_str("objective:\n    minimize: true"),
+            Err(Error::InvalidYaml(_))
+        ));
+    }
+}
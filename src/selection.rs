@@ -85,7 +85,7 @@ mod tests {
         let individuals_ordered = [0, 1];
 
         const N: usize = 10000;
-        let mut counts = vec![0, 0];
+        let mut counts = [0, 0];
         for _ in 0..N {
             let selected_individual =
                 sut.select_value(&individuals_ordered, selection_pressure, &mut rng);
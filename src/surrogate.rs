@@ -0,0 +1,128 @@
+use crate::distance;
+use crate::spec::Spec;
+use crate::value::Value;
+use itertools::Itertools;
+use std::collections::VecDeque;
+
+const MAX_NUM_SAMPLES: usize = 1000;
+
+// k-nearest-neighbour regression based on the structural value distance, which normalizes
+// numeric leaves by their scale and treats categorical choices like a one-hot encoding
+pub struct KnnSurrogate {
+    num_neighbors: usize,
+    samples: VecDeque<(Value, f64)>,
+}
+
+impl KnnSurrogate {
+    pub fn new(num_neighbors: usize) -> Self {
+        Self {
+            num_neighbors,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn train(&mut self, value: Value, obj_func_val: f64) {
+        if self.samples.len() == MAX_NUM_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back((value, obj_func_val));
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.samples.len() >= self.num_neighbors
+    }
+
+    pub fn predict(&self, spec: &Spec, value: &Value) -> f64 {
+        let nearest = self
+            .samples
+            .iter()
+            .map(|(sample_value, obj_func_val)| {
+                (distance::distance(spec, value, sample_value), *obj_func_val)
+            })
+            .sorted_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0))
+            .take(self.num_neighbors)
+            .collect_vec();
+
+        let exact_matches = nearest
+            .iter()
+            .filter(|(dist, _)| *dist == 0.0)
+            .map(|(_, obj_func_val)| *obj_func_val)
+            .collect_vec();
+
+        if !exact_matches.is_empty() {
+            return exact_matches.iter().sum::<f64>() / exact_matches.len() as f64;
+        }
+
+        let (weighted_sum, weight_sum) = nearest.iter().fold(
+            (0.0, 0.0),
+            |(weighted_sum, weight_sum), (dist, obj_func_val)| {
+                let weight = 1.0 / dist;
+                (weighted_sum + weight * obj_func_val, weight_sum + weight)
+            },
+        );
+
+        weighted_sum / weight_sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec;
+    use crate::value::Node;
+    use float_cmp::assert_approx_eq;
+
+    const REAL_SPEC: Spec = spec::Spec(spec::Node::Real {
+        init: 0.0,
+        scale: 1.0,
+        min: None,
+        max: None,
+    });
+
+    fn real(value: f64) -> Value {
+        Value(Node::Real(value))
+    }
+
+    #[test]
+    fn readiness() {
+        let mut sut = KnnSurrogate::new(2);
+        assert!(!sut.is_ready());
+        sut.train(real(0.0), 1.0);
+        assert!(!sut.is_ready());
+        sut.train(real(1.0), 1.0);
+        assert!(sut.is_ready());
+    }
+
+    #[test]
+    fn exact_match() {
+        let mut sut = KnnSurrogate::new(2);
+        sut.train(real(0.0), 1.0);
+        sut.train(real(1.0), 2.0);
+
+        assert_approx_eq!(f64, sut.predict(&REAL_SPEC, &real(1.0)), 2.0);
+    }
+
+    #[test]
+    fn inverse_distance_weighting() {
+        let mut sut = KnnSurrogate::new(2);
+        sut.train(real(0.0), 1.0);
+        sut.train(real(3.0), 4.0);
+        sut.train(real(100.0), 100.0);
+
+        // weights 1 and 1/2 for the two nearest neighbours
+        assert_approx_eq!(f64, sut.predict(&REAL_SPEC, &real(1.0)), 2.0);
+    }
+
+    #[test]
+    fn oldest_samples_dropped() {
+        let mut sut = KnnSurrogate::new(1);
+        sut.train(real(0.0), 1.0);
+
+        for _ in 0..MAX_NUM_SAMPLES {
+            sut.train(real(10.0), 2.0);
+        }
+
+        assert_approx_eq!(f64, sut.predict(&REAL_SPEC, &real(0.0)), 2.0);
+    }
+}
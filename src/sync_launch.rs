@@ -1,9 +1,13 @@
+use crate::affinity;
 use crate::async_launch;
 use crate::detailed_report::DetailedReportItem;
 use crate::error::Error;
-use crate::message::Command;
+use crate::message::{Command, Report};
 use crate::meta::AlgoConfig;
 use crate::meta::AsyncObjectiveFunction;
+use crate::meta::EvalContext;
+use crate::meta::ObjFuncResult;
+use crate::pruning::IntermediateReporter;
 use crate::result::FinalReport;
 use crate::termination;
 use crate::termination::TerminationCriterion;
@@ -13,13 +17,11 @@ use ctrlc;
 use futures::channel::mpsc;
 use futures::channel::mpsc::Receiver;
 use futures::executor;
-use futures::future::Either;
 use futures::pin_mut;
 use futures::select;
 use futures::sink::SinkExt;
 use futures::FutureExt;
 use futures::StreamExt;
-use futures_timer::Delay;
 use log::info;
 use std::panic;
 use std::path::PathBuf;
@@ -80,6 +82,7 @@ async fn write_best_seen_file(
 async fn handle_detailed_report_items(
     detailed_reporting_file_info: Option<&DetailedReportingFileInfo>,
     mut item_receiver: Receiver<DetailedReportItem>,
+    maximize: bool,
 ) -> Result<(), Error> {
     if let Some(file_info) = detailed_reporting_file_info {
         let mut detailed_report_file = File::create(&file_info.detailed_report_file_path)
@@ -100,10 +103,20 @@ async fn handle_detailed_report_items(
                 .write_all(item.to_csv_row().as_bytes())
                 .await?;
 
-            if let Some(item_obj_func_val) = item.obj_func_val {
+            // lower budget results are not comparable to full budget ones
+            if let (Some(item_obj_func_val), None, true) = (
+                item.obj_func_val,
+                item.constraint_violation,
+                item.full_fidelity,
+            ) {
                 let new_best_seen = if let Some(ref best_seen) = best_seen {
                     let best_obj_func_val = best_seen.obj_func_val.unwrap();
-                    item_obj_func_val < best_obj_func_val
+
+                    if maximize {
+                        item_obj_func_val > best_obj_func_val
+                    } else {
+                        item_obj_func_val < best_obj_func_val
+                    }
                 } else {
                     true
                 };
@@ -137,6 +150,36 @@ pub fn launch_with_async_obj_func<F, T>(
 where
     F: AsyncObjectiveFunction,
     T: IntoIterator<Item = TerminationCriterion>,
+{
+    launch_with_report_handler(
+        spec,
+        obj_func,
+        algo_config,
+        termination_criteria,
+        explicit_init_value_json,
+        in_process_computation,
+        detailed_reporting_file_info,
+        |_| (),
+    )
+}
+
+// The report handler is called with each progress event, on the calling thread. Events are
+// buffered while it lags behind, so it never slows down the optimization
+#[allow(clippy::too_many_arguments)]
+pub fn launch_with_report_handler<F, T, H>(
+    spec: Spec,
+    obj_func: F,
+    algo_config: AlgoConfig,
+    termination_criteria: T,
+    explicit_init_value_json: Option<serde_json::Value>,
+    in_process_computation: bool,
+    detailed_reporting_file_info: Option<&DetailedReportingFileInfo>,
+    mut report_handler: H,
+) -> Result<FinalReport, Error>
+where
+    F: AsyncObjectiveFunction,
+    T: IntoIterator<Item = TerminationCriterion>,
+    H: FnMut(Report),
 {
     let mut runtime_builder = if in_process_computation {
         let mut builder = runtime::Builder::new_multi_thread();
@@ -146,25 +189,65 @@ where
         runtime::Builder::new_current_thread()
     };
 
+    // the calling thread runs the controller, the threads of a multi-threaded runtime the
+    // evaluations, so that they get the CPUs of the slots rather than the reserved ones
+    let cpu_partition = algo_config
+        .cpu_pinning
+        .as_ref()
+        .map(|cpu_pinning| affinity::partition(cpu_pinning, algo_config.num_concurrent))
+        .transpose()?;
+
+    let _pinned_thread = match cpu_partition {
+        Some((ref reserved_cpus, ref slot_cpu_sets)) => {
+            let runtime_cpus = if in_process_computation {
+                slot_cpu_sets.concat()
+            } else {
+                reserved_cpus.clone()
+            };
+
+            if !runtime_cpus.is_empty() {
+                runtime_builder.on_thread_start(move || {
+                    affinity::confine_current_thread(&runtime_cpus).ok();
+                });
+            }
+
+            if reserved_cpus.is_empty() {
+                None
+            } else {
+                Some(affinity::pin_current_thread(reserved_cpus)?)
+            }
+        }
+        None => None,
+    };
+
     let termination_criteria = termination::compile(termination_criteria)?;
 
-    let (mut cmd_sender, cmd_recv) = mpsc::channel::<Command>(CHANNEL_BUF_SIZE);
+    let (cmd_sender, cmd_recv) = mpsc::channel::<Command>(CHANNEL_BUF_SIZE);
     let (detailed_report_sender, detailed_report_recv) =
         mpsc::channel::<DetailedReportItem>(CHANNEL_BUF_SIZE);
+    let (report_sender, report_recv) = mpsc::unbounded::<Report>();
+
+    let maximize = algo_config.objective.maximize;
 
-    let launch_fut = async_launch::launch(
+    let launch_fut = async_launch::launch_with_slot_cpu_sets(
         spec,
         obj_func,
         algo_config,
         cmd_recv,
         detailed_report_sender,
-        termination_criteria.max_num_obj_func_eval,
-        termination_criteria.target_obj_func_val,
+        report_sender,
+        termination_criteria.criterion,
         explicit_init_value_json,
+        cpu_partition.map(|(_, slot_cpu_sets)| slot_cpu_sets),
     );
 
     let detailed_reporting_fut =
-        handle_detailed_report_items(detailed_reporting_file_info, detailed_report_recv);
+        handle_detailed_report_items(detailed_reporting_file_info, detailed_report_recv, maximize);
+
+    let reporting_fut = report_recv.for_each(|report| {
+        report_handler(report);
+        futures::future::ready(())
+    });
 
     if termination_criteria.terminate_on_signal {
         let mut sender_for_handler = cmd_sender.clone();
@@ -179,29 +262,22 @@ where
         .build()
         .unwrap()
         .block_on(async {
-            let timeout_fut = if let Some(terminate_after) = termination_criteria.terminate_after {
-                Either::Left(Delay::new(terminate_after).fuse())
-            } else {
-                Either::Right(futures::future::pending())
-            };
-
             let launch_fut = launch_fut.fuse();
             let detailed_reporting_fut = detailed_reporting_fut.fuse();
-            pin_mut!(timeout_fut, launch_fut, detailed_reporting_fut);
+            let reporting_fut = reporting_fut.fuse();
+            pin_mut!(launch_fut, detailed_reporting_fut, reporting_fut);
 
             loop {
                 select! {
-                    () = &mut timeout_fut => {
-                        info!("Abort time reached");
-                        cmd_sender.send(Command::Terminate).await.unwrap();
-                    }
                     res = &mut launch_fut => {
                         detailed_reporting_fut.await?;
+                        reporting_fut.await;
                         return res;
                     }
                     res = &mut detailed_reporting_fut => {
                         res?;
                     }
+                    () = &mut reporting_fut => (),
                 }
             }
         })
@@ -231,15 +307,17 @@ where
         &self,
         value: serde_json::Value,
         mut abort_sig_recv: async_broadcast::Receiver<()>,
-        _seed: u64,
-        _individual_id: usize,
-    ) -> Result<Option<f64>, Error> {
+        eval_ctx: &EvalContext,
+        _intermediate_reporter: &IntermediateReporter,
+    ) -> Result<ObjFuncResult, Error> {
         let obj_func = self.obj_func.clone();
+        let budget = eval_ctx.budget;
 
-        let join_handle = tokio::spawn(async move { obj_func.evaluate(value) });
+        let join_handle =
+            tokio::spawn(async move { obj_func.evaluate_with_constraints(value, budget) });
 
         tokio::select! {
-            _ = abort_sig_recv.recv() => return Ok(None),
+            _ = abort_sig_recv.recv() => return Ok(ObjFuncResult::rejected()),
             join_result = join_handle => match join_result {
             Ok(res) => Ok(res),
             Err(join_error) if join_error.is_panic() => {
@@ -1,51 +1,589 @@
 use self::TerminationCriterion::*;
 use crate::error::Error;
-use std::time::Duration;
+use itertools::Itertools;
+use parse_duration::parse;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TerminationCriterion {
     NumObjFuncEval(usize),
     TargetObjFuncVal(f64),
     TerminateAfter(Duration),
+    NoImprovement { evals: usize, min_delta: f64 },
+    NoImprovementFor(Duration),
+    All(Vec<TerminationCriterion>),
+    Any(Vec<TerminationCriterion>),
     Signal,
 }
 
+impl Display for TerminationCriterion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fmt_operand = |criterion: &TerminationCriterion| match criterion {
+            All(_) | Any(_) => format!("({})", criterion),
+            _ => criterion.to_string(),
+        };
+
+        match self {
+            NumObjFuncEval(num_eval) => write!(f, "evals({})", num_eval),
+            TargetObjFuncVal(target) => write!(f, "target({})", target),
+            TerminateAfter(duration) => write!(f, "after({:?})", duration),
+            NoImprovement { evals, min_delta } => {
+                write!(f, "no_improvement({}, {})", evals, min_delta)
+            }
+            NoImprovementFor(duration) => write!(f, "no_improvement_for({:?})", duration),
+            All(criteria) => write!(f, "{}", criteria.iter().map(fmt_operand).join(" and ")),
+            Any(criteria) => write!(f, "{}", criteria.iter().map(fmt_operand).join(" or ")),
+            Signal => write!(f, "signal"),
+        }
+    }
+}
+
+impl TerminationCriterion {
+    // upper bound on the number of evaluations, used to avoid scheduling evaluations in excess
+    pub(crate) fn max_num_obj_func_eval(&self) -> Option<usize> {
+        match self {
+            NumObjFuncEval(num_eval) => Some(*num_eval),
+            All(criteria) => criteria
+                .iter()
+                .map(TerminationCriterion::max_num_obj_func_eval)
+                .collect::<Option<Vec<_>>>()
+                .and_then(|bounds| bounds.into_iter().max()),
+            Any(criteria) => criteria
+                .iter()
+                .filter_map(TerminationCriterion::max_num_obj_func_eval)
+                .min(),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) struct CompiledTerminationCriteria {
-    pub max_num_obj_func_eval: Option<usize>,
-    pub target_obj_func_val: Option<f64>,
-    pub terminate_after: Option<Duration>,
+    pub criterion: TerminationCriterion,
     pub terminate_on_signal: bool,
 }
 
+// Top level criteria are OR-ed. Signal handling is set up outside of the expression.
 pub(crate) fn compile<T>(termination_criteria: T) -> Result<CompiledTerminationCriteria, Error>
 where
     T: IntoIterator<Item = TerminationCriterion>,
 {
-    let mut max_num_obj_func_eval = None;
-    let mut target_obj_func_val = None;
-    let mut terminate_after = None;
+    let mut criteria = Vec::new();
     let mut terminate_on_signal = false;
 
     for criterion in termination_criteria {
         match criterion {
-            NumObjFuncEval(num_eval) if max_num_obj_func_eval.is_none() => {
-                max_num_obj_func_eval = Some(num_eval)
-            }
-            TargetObjFuncVal(target) if target_obj_func_val.is_none() => {
-                target_obj_func_val = Some(target)
-            }
-            TerminateAfter(duration) if terminate_after.is_none() => {
-                terminate_after = Some(duration)
-            }
-            Signal if !terminate_on_signal => terminate_on_signal = true,
-            _ => return Err(Error::ConflictingTerminationCriteria),
+            Signal if terminate_on_signal => return Err(Error::ConflictingTerminationCriteria),
+            Signal => terminate_on_signal = true,
+            criterion => criteria.push(criterion),
         }
     }
 
+    let criterion = if criteria.len() == 1 {
+        criteria.pop().unwrap()
+    } else {
+        Any(criteria)
+    };
+
     Ok(CompiledTerminationCriteria {
-        max_num_obj_func_eval,
-        target_obj_func_val,
-        terminate_after,
+        criterion,
         terminate_on_signal,
     })
 }
+
+pub fn parse_expr(expr_str: &str) -> Result<TerminationCriterion, Error> {
+    let invalid_expr = || Error::InvalidTerminationExpr(expr_str.to_string());
+
+    let mut parser = ExprParser {
+        tokens: tokenize(expr_str),
+        pos: 0,
+    };
+
+    let criterion = parser.parse_or().ok_or_else(invalid_expr)?;
+
+    if parser.pos == parser.tokens.len() {
+        Ok(criterion)
+    } else {
+        Err(invalid_expr())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Comma,
+    Word(String),
+}
+
+fn tokenize(expr_str: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+
+    for c in expr_str.chars() {
+        let token = match c {
+            '(' => Some(Token::LParen),
+            ')' => Some(Token::RParen),
+            ',' => Some(Token::Comma),
+            _ => None,
+        };
+
+        if token.is_some() || c.is_whitespace() {
+            if !word.is_empty() {
+                tokens.push(Token::Word(std::mem::take(&mut word)));
+            }
+        } else {
+            word.push(c);
+        }
+
+        tokens.extend(token);
+    }
+
+    if !word.is_empty() {
+        tokens.push(Token::Word(word));
+    }
+
+    tokens
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, token: Token) -> Option<()> {
+        (self.next()? == token).then_some(())
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word == keyword)
+    }
+
+    fn parse_or(&mut self) -> Option<TerminationCriterion> {
+        let mut operands = vec![self.parse_and()?];
+
+        while self.is_keyword("or") {
+            self.pos += 1;
+            operands.push(self.parse_and()?);
+        }
+
+        Some(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Any(operands)
+        })
+    }
+
+    fn parse_and(&mut self) -> Option<TerminationCriterion> {
+        let mut operands = vec![self.parse_operand()?];
+
+        while self.is_keyword("and") {
+            self.pos += 1;
+            operands.push(self.parse_operand()?);
+        }
+
+        Some(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            All(operands)
+        })
+    }
+
+    fn parse_operand(&mut self) -> Option<TerminationCriterion> {
+        match self.next()? {
+            Token::LParen => {
+                let criterion = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Some(criterion)
+            }
+            Token::Word(name) => {
+                self.expect(Token::LParen)?;
+                let args = self.parse_args()?;
+                make_criterion(&name, &args)
+            }
+            _ => None,
+        }
+    }
+
+    // arguments may consist of several words, e.g. "1 hour"
+    fn parse_args(&mut self) -> Option<Vec<String>> {
+        let mut args = Vec::new();
+        let mut arg_words = Vec::new();
+
+        loop {
+            match self.next()? {
+                Token::Word(word) => arg_words.push(word),
+                Token::Comma => args.push(std::mem::take(&mut arg_words).join(" ")),
+                Token::RParen => {
+                    args.push(arg_words.join(" "));
+                    return Some(args);
+                }
+                Token::LParen => return None,
+            }
+        }
+    }
+}
+
+fn make_criterion(name: &str, args: &[String]) -> Option<TerminationCriterion> {
+    match (name, args) {
+        ("evals", [num_eval]) => Some(NumObjFuncEval(num_eval.parse().ok()?)),
+        ("target", [target]) => Some(TargetObjFuncVal(target.parse().ok()?)),
+        ("after", [duration]) => Some(TerminateAfter(parse(duration).ok()?)),
+        ("no_improvement", [evals]) => Some(NoImprovement {
+            evals: evals.parse().ok()?,
+            min_delta: 0.0,
+        }),
+        ("no_improvement", [evals, min_delta]) => Some(NoImprovement {
+            evals: evals.parse().ok()?,
+            min_delta: min_delta.parse().ok()?,
+        }),
+        ("no_improvement_for", [duration]) => Some(NoImprovementFor(parse(duration).ok()?)),
+        _ => None,
+    }
+}
+
+// Tracks the best seen objective function value in the direction of optimization, i.e. lower is
+// better. Only improvements by more than min delta count.
+struct ImprovementTracker {
+    min_delta: f64,
+    ref_obj_func_val: Option<f64>,
+    num_eval_since_improvement: usize,
+    last_improvement_ts: Instant,
+}
+
+impl ImprovementTracker {
+    fn new(min_delta: f64, start_ts: Instant) -> Self {
+        Self {
+            min_delta,
+            ref_obj_func_val: None,
+            num_eval_since_improvement: 0,
+            last_improvement_ts: start_ts,
+        }
+    }
+
+    fn record_eval(&mut self, best_seen_obj_func_val: Option<f64>) {
+        self.num_eval_since_improvement += 1;
+
+        if let Some(obj_func_val) = best_seen_obj_func_val {
+            if self
+                .ref_obj_func_val
+                .is_none_or(|ref_obj_func_val| ref_obj_func_val - obj_func_val > self.min_delta)
+            {
+                self.ref_obj_func_val = Some(obj_func_val);
+                self.num_eval_since_improvement = 0;
+                self.last_improvement_ts = Instant::now();
+            }
+        }
+    }
+}
+
+enum ExprNode {
+    NumObjFuncEval(usize),
+    TargetObjFuncVal(f64),
+    Deadline(Instant),
+    NoImprovement(usize, ImprovementTracker),
+    NoImprovementFor(Duration, ImprovementTracker),
+    All(Vec<TerminationExpr>),
+    Any(Vec<TerminationExpr>),
+}
+
+// Stateful counterpart of a termination criterion, evaluated by the controller. Objective
+// function values passed in are oriented such that lower is better.
+pub(crate) struct TerminationExpr {
+    criterion: TerminationCriterion,
+    node: ExprNode,
+}
+
+impl TerminationExpr {
+    pub fn new(criterion: &TerminationCriterion, maximize: bool) -> Result<Self, Error> {
+        Self::with_start_ts(criterion, maximize, Instant::now())
+    }
+
+    fn with_start_ts(
+        criterion: &TerminationCriterion,
+        maximize: bool,
+        start_ts: Instant,
+    ) -> Result<Self, Error> {
+        let make_operands = |criteria: &[TerminationCriterion]| {
+            criteria
+                .iter()
+                .map(|criterion| Self::with_start_ts(criterion, maximize, start_ts))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let node = match criterion {
+            NumObjFuncEval(num_eval) => ExprNode::NumObjFuncEval(*num_eval),
+            TargetObjFuncVal(target) => {
+                ExprNode::TargetObjFuncVal(if maximize { -target } else { *target })
+            }
+            TerminateAfter(duration) => ExprNode::Deadline(start_ts + *duration),
+            NoImprovement { evals, min_delta } => {
+                if !min_delta.is_finite() || *min_delta < 0.0 {
+                    return Err(Error::InvalidNoImprovementMinDelta);
+                }
+
+                ExprNode::NoImprovement(*evals, ImprovementTracker::new(*min_delta, start_ts))
+            }
+            NoImprovementFor(duration) => {
+                ExprNode::NoImprovementFor(*duration, ImprovementTracker::new(0.0, start_ts))
+            }
+            All(criteria) => ExprNode::All(make_operands(criteria)?),
+            Any(criteria) => ExprNode::Any(make_operands(criteria)?),
+            Signal => return Err(Error::SignalCriterionNotComposable),
+        };
+
+        Ok(Self {
+            criterion: criterion.clone(),
+            node,
+        })
+    }
+
+    pub fn record_eval(&mut self, best_seen_obj_func_val: Option<f64>) {
+        match &mut self.node {
+            ExprNode::NoImprovement(_, tracker) | ExprNode::NoImprovementFor(_, tracker) => {
+                tracker.record_eval(best_seen_obj_func_val)
+            }
+            ExprNode::All(operands) | ExprNode::Any(operands) => operands
+                .iter_mut()
+                .for_each(|operand| operand.record_eval(best_seen_obj_func_val)),
+            _ => (),
+        }
+    }
+
+    // returns the criterion that fired, if any
+    pub fn fired(
+        &self,
+        num_eval: usize,
+        best_seen_obj_func_val: Option<f64>,
+    ) -> Option<&TerminationCriterion> {
+        let now = Instant::now();
+
+        let is_fired = match &self.node {
+            ExprNode::NumObjFuncEval(max_num_eval) => num_eval >= *max_num_eval,
+            ExprNode::TargetObjFuncVal(target) => {
+                best_seen_obj_func_val.is_some_and(|obj_func_val| obj_func_val <= *target)
+            }
+            ExprNode::Deadline(deadline) => now >= *deadline,
+            ExprNode::NoImprovement(evals, tracker) => tracker.num_eval_since_improvement >= *evals,
+            ExprNode::NoImprovementFor(duration, tracker) => {
+                now >= tracker.last_improvement_ts + *duration
+            }
+            ExprNode::All(operands) => operands
+                .iter()
+                .all(|operand| operand.fired(num_eval, best_seen_obj_func_val).is_some()),
+            ExprNode::Any(operands) => {
+                return operands
+                    .iter()
+                    .find_map(|operand| operand.fired(num_eval, best_seen_obj_func_val))
+            }
+        };
+
+        is_fired.then_some(&self.criterion)
+    }
+
+    // next point in time at which a time based criterion may fire
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let now = Instant::now();
+
+        let deadline = match &self.node {
+            ExprNode::Deadline(deadline) => Some(*deadline),
+            ExprNode::NoImprovementFor(duration, tracker) => {
+                Some(tracker.last_improvement_ts + *duration)
+            }
+            ExprNode::All(operands) | ExprNode::Any(operands) => {
+                return operands
+                    .iter()
+                    .filter_map(TerminationExpr::next_deadline)
+                    .min()
+            }
+            _ => None,
+        };
+
+        deadline.filter(|deadline| *deadline > now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_sut(criterion: TerminationCriterion) -> TerminationExpr {
+        TerminationExpr::new(&criterion, false).unwrap()
+    }
+
+    #[test]
+    fn no_improvement() {
+        let mut sut = make_sut(NoImprovement {
+            evals: 2,
+            min_delta: 0.1,
+        });
+
+        sut.record_eval(None);
+        sut.record_eval(Some(1.0));
+        sut.record_eval(Some(0.95));
+        assert!(sut.fired(3, Some(0.95)).is_none());
+
+        sut.record_eval(Some(0.9));
+        assert!(sut.fired(4, Some(0.9)).is_some());
+
+        sut.record_eval(Some(0.8));
+        assert!(sut.fired(5, Some(0.8)).is_none());
+    }
+
+    #[test]
+    fn no_improvement_for() {
+        let mut sut = make_sut(NoImprovementFor(Duration::from_secs(60)));
+        let initial_deadline = sut.next_deadline().unwrap();
+
+        sut.record_eval(Some(1.0));
+        sut.record_eval(Some(1.0));
+        assert!(sut.fired(2, Some(1.0)).is_none());
+
+        let deadline = sut.next_deadline().unwrap();
+        assert!(deadline >= initial_deadline);
+
+        sut.record_eval(Some(1.0));
+        assert_eq!(sut.next_deadline().unwrap(), deadline);
+    }
+
+    #[test]
+    fn target_when_maximizing() {
+        let sut = TerminationExpr::new(&TargetObjFuncVal(0.9), true).unwrap();
+
+        assert!(sut.fired(1, Some(-0.8)).is_none());
+        assert!(sut.fired(1, Some(-0.95)).is_some());
+    }
+
+    #[test]
+    fn composition() {
+        let all = All(vec![
+            NumObjFuncEval(5),
+            Any(vec![TargetObjFuncVal(0.1), NumObjFuncEval(10)]),
+        ]);
+        let sut = make_sut(all.clone());
+
+        assert!(sut.fired(4, Some(0.05)).is_none());
+        assert!(sut.fired(5, Some(0.2)).is_none());
+        assert_eq!(sut.fired(5, Some(0.05)), Some(&all));
+        assert_eq!(sut.fired(10, Some(0.2)), Some(&all));
+
+        let sut = make_sut(Any(vec![TargetObjFuncVal(0.1), NumObjFuncEval(10)]));
+        assert_eq!(sut.fired(10, Some(0.2)), Some(&NumObjFuncEval(10)));
+    }
+
+    #[test]
+    fn next_deadline() {
+        let sut = make_sut(All(vec![
+            TerminateAfter(Duration::ZERO),
+            TerminateAfter(Duration::from_secs(10)),
+            NoImprovementFor(Duration::from_secs(20)),
+        ]));
+
+        let next_deadline = sut.next_deadline().unwrap();
+        assert!(next_deadline <= Instant::now() + Duration::from_secs(10));
+        assert!(next_deadline > Instant::now() + Duration::from_secs(9));
+    }
+
+    #[test]
+    fn max_num_obj_func_eval() {
+        assert_eq!(
+            Any(vec![NumObjFuncEval(10), NumObjFuncEval(5), Signal]).max_num_obj_func_eval(),
+            Some(5)
+        );
+        assert_eq!(
+            All(vec![NumObjFuncEval(10), NumObjFuncEval(5)]).max_num_obj_func_eval(),
+            Some(10)
+        );
+        assert_eq!(
+            All(vec![NumObjFuncEval(10), TargetObjFuncVal(0.1)]).max_num_obj_func_eval(),
+            None
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let criterion = parse_expr(
+            "evals(500) and (target(1e-3) or no_improvement(200, 0.01)) or after(1 hour)",
+        )
+        .unwrap();
+
+        let expected = Any(vec![
+            All(vec![
+                NumObjFuncEval(500),
+                Any(vec![
+                    TargetObjFuncVal(1e-3),
+                    NoImprovement {
+                        evals: 200,
+                        min_delta: 0.01,
+                    },
+                ]),
+            ]),
+            TerminateAfter(Duration::from_secs(3600)),
+        ]);
+
+        assert_eq!(criterion, expected);
+        assert_eq!(parse_expr(&criterion.to_string()).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_durations() {
+        let criterion = parse_expr("no_improvement_for(150ms) and after(2h)").unwrap();
+
+        assert_eq!(
+            criterion,
+            All(vec![
+                NoImprovementFor(Duration::from_millis(150)),
+                TerminateAfter(Duration::from_secs(7200)),
+            ])
+        );
+        assert_eq!(parse_expr(&criterion.to_string()).unwrap(), criterion);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for expr_str in ["", "evals(10", "evals(ten)", "evals(10) and", "unknown(1)"] {
+            assert!(matches!(
+                parse_expr(expr_str),
+                Err(Error::InvalidTerminationExpr(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_min_delta() {
+        let result = TerminationExpr::new(
+            &NoImprovement {
+                evals: 10,
+                min_delta: -1.0,
+            },
+            false,
+        );
+
+        assert!(matches!(result, Err(Error::InvalidNoImprovementMinDelta)));
+    }
+
+    #[test]
+    fn nested_signal() {
+        let result = TerminationExpr::new(&All(vec![Signal]), false);
+        assert!(matches!(result, Err(Error::SignalCriterionNotComposable)));
+    }
+
+    #[test]
+    fn duplicate_signal() {
+        assert!(matches!(
+            compile([Signal, Signal]),
+            Err(Error::ConflictingTerminationCriteria)
+        ));
+    }
+}
@@ -21,10 +21,24 @@ pub enum Node {
     Const,
 }
 
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
 impl Value {
     pub fn to_json(&self) -> serde_json::Value {
         self.0.to_json()
     }
+
+    // JSON object keys are sorted, which makes the JSON text canonical. FNV-1a is used rather
+    // than the std hasher, because hashes are persisted and must be stable across builds.
+    pub fn canonical_hash(&self) -> u64 {
+        self.to_json()
+            .to_string()
+            .bytes()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
 }
 
 impl Node {
@@ -97,4 +111,34 @@ mod tests {
         let expect_json_text = r#"{"a":2.0,"b":1,"c":{"0":true},"d":[1,3]}"#;
         assert_eq!(value.to_json().to_string(), expect_json_text);
     }
+
+    #[test]
+    fn canonical_hash() {
+        let make_value = |keys: &[&str], b_val: i64| {
+            Value(Node::Sub(
+                keys.iter()
+                    .map(|key| {
+                        let node = if *key == "b" {
+                            Node::Int(b_val)
+                        } else {
+                            Node::Bool(true)
+                        };
+                        (key.to_string(), Box::new(node))
+                    })
+                    .collect(),
+            ))
+        };
+
+        let value = make_value(&["a", "b", "c"], 1);
+
+        assert_eq!(
+            value.canonical_hash(),
+            make_value(&["c", "b", "a"], 1).canonical_hash()
+        );
+        assert_ne!(
+            value.canonical_hash(),
+            make_value(&["a", "b", "c"], 2).canonical_hash()
+        );
+        assert_eq!(value.canonical_hash(), 7447186331729053198);
+    }
 }
@@ -0,0 +1,190 @@
+use crate::error::Error;
+use crate::meta::ObjFuncResult;
+use std::path::{self, Path, PathBuf};
+use std::sync::Mutex;
+use tokio::fs;
+
+pub const WORK_DIR_ENV_VAR: &str = "CAMBRIAN_WORK_DIR";
+
+// Fresh working directories for objective function processes. If a retention limit is set,
+// only the directories of the best evaluations and of failed ones are kept.
+#[derive(Debug)]
+pub struct WorkDirs {
+    root: PathBuf,
+    keep_best: Option<usize>,
+    maximize: bool,
+    // oriented objective function values, lower is better, sorted in ascending order
+    best: Mutex<Vec<(f64, PathBuf)>>,
+}
+
+impl WorkDirs {
+    pub fn new(root: &Path, keep_best: Option<usize>, maximize: bool) -> Result<Self, Error> {
+        Ok(Self {
+            root: path::absolute(root)?,
+            keep_best,
+            maximize,
+            best: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub(crate) async fn create(&self, name: &str) -> Result<PathBuf, Error> {
+        let work_dir = self.root.join(name);
+
+        fs::create_dir_all(&work_dir)
+            .await
+            .map_err(|err| Error::UnableToCreateWorkDir {
+                path: work_dir.clone(),
+                source: err,
+            })?;
+
+        Ok(work_dir)
+    }
+
+    // lower is better for oriented values
+    fn orient(&self, obj_func_val: f64) -> f64 {
+        if self.maximize {
+            -obj_func_val
+        } else {
+            obj_func_val
+        }
+    }
+
+    // a directory retained here is deleted once enough better ones are seen
+    async fn retain_if_among_best(
+        &self,
+        work_dir: PathBuf,
+        obj_func_val: f64,
+    ) -> Result<(), Error> {
+        if let Some(keep_best) = self.keep_best {
+            let oriented = self.orient(obj_func_val);
+
+            let evicted = {
+                let mut best = self.best.lock().unwrap();
+                let idx = best.partition_point(|(val, _)| *val <= oriented);
+                best.insert(idx, (oriented, work_dir));
+
+                if best.len() > keep_best {
+                    best.pop().map(|(_, work_dir)| work_dir)
+                } else {
+                    None
+                }
+            };
+
+            if let Some(evicted) = evicted {
+                fs::remove_dir_all(evicted).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // directories of failed evaluations are kept for inspection, those of stopped ones are not
+    pub(crate) async fn retain(
+        &self,
+        work_dir: PathBuf,
+        stopped: bool,
+        results: &[ObjFuncResult],
+    ) -> Result<(), Error> {
+        if stopped {
+            return self.discard(work_dir).await;
+        }
+
+        if results.iter().any(|result| result.obj_func_val.is_none()) {
+            return Ok(());
+        }
+
+        let best_feasible = results
+            .iter()
+            .filter(|result| result.constraint_violation.unwrap_or(0.0) <= 0.0)
+            .filter_map(|result| result.obj_func_val)
+            .min_by(|lhs, rhs| self.orient(*lhs).total_cmp(&self.orient(*rhs)));
+
+        match best_feasible {
+            Some(obj_func_val) => self.retain_if_among_best(work_dir, obj_func_val).await,
+            None => self.discard(work_dir).await,
+        }
+    }
+
+    pub(crate) async fn discard(&self, work_dir: PathBuf) -> Result<(), Error> {
+        if self.keep_best.is_some() {
+            fs::remove_dir_all(work_dir).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn make_root(name: &str) -> PathBuf {
+        let root =
+            env::temp_dir().join(format!("cambrian_work_dir_{}_{}", name, std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+        root
+    }
+
+    #[tokio::test]
+    async fn keep_best() {
+        let root = make_root("keep_best");
+        let sut = WorkDirs::new(&root, Some(2), false).unwrap();
+
+        for (name, obj_func_val) in [("a", 3.0), ("b", 1.0), ("c", 2.0), ("d", 4.0)] {
+            let work_dir = sut.create(name).await.unwrap();
+            sut.retain_if_among_best(work_dir, obj_func_val)
+                .await
+                .unwrap();
+        }
+
+        let failed = sut.create("e").await.unwrap();
+        let discarded = sut.create("f").await.unwrap();
+        sut.discard(discarded).await.unwrap();
+
+        assert!(!root.join("a").exists());
+        assert!(root.join("b").exists());
+        assert!(root.join("c").exists());
+        assert!(!root.join("d").exists());
+        assert!(failed.exists());
+        assert!(!root.join("f").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn keep_best_maximize() {
+        let root = make_root("keep_best_maximize");
+        let sut = WorkDirs::new(&root, Some(1), true).unwrap();
+
+        for (name, obj_func_val) in [("a", 1.0), ("b", 2.0)] {
+            let work_dir = sut.create(name).await.unwrap();
+            sut.retain_if_among_best(work_dir, obj_func_val)
+                .await
+                .unwrap();
+        }
+
+        assert!(!root.join("a").exists());
+        assert!(root.join("b").exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn keep_all() {
+        let root = make_root("keep_all");
+        let sut = WorkDirs::new(&root, None, false).unwrap();
+
+        let work_dir = sut.create("a").await.unwrap();
+        sut.retain_if_among_best(work_dir.clone(), 1.0)
+            .await
+            .unwrap();
+        let discarded = sut.create("b").await.unwrap();
+        sut.discard(discarded.clone()).await.unwrap();
+
+        assert!(work_dir.exists());
+        assert!(discarded.exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
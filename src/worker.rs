@@ -0,0 +1,180 @@
+use crate::distributed::{self, CoordinatorMessage, Endpoint, Job, WorkerMessage};
+use crate::error::Error;
+use crate::meta::{AlgoConfigBuilder, AsyncObjectiveFunction, EvalContext};
+use crate::pruning::IntermediateReporter;
+use log::{info, warn};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+// Evaluates jobs received from the coordinator, at most capacity at a time, until the
+// coordinator closes the connection. Evaluations still running then are aborted.
+pub async fn run<F>(
+    endpoint: &Endpoint,
+    capacity: usize,
+    heartbeat_interval: Duration,
+    obj_func: F,
+) -> Result<(), Error>
+where
+    F: AsyncObjectiveFunction + Send + 'static,
+{
+    // jobs are single evaluations, which have a budget if the coordinator uses multi-fidelity
+    obj_func.validate(&AlgoConfigBuilder::new().fidelity(1.0, 1.0, 2).build()?)?;
+
+    let (reader, mut writer) = distributed::connect(endpoint).await.map_err(|err| {
+        Error::UnableToConnectToCoordinator {
+            endpoint: endpoint.to_string(),
+            source: err,
+        }
+    })?;
+
+    info!("Connected to coordinator at {}", endpoint);
+
+    let (sender, mut message_receiver) = mpsc::unbounded_channel();
+    sender.send(WorkerMessage::Hello { capacity }).ok();
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = message_receiver.recv().await {
+            distributed::write_message(&mut writer, &message).await?;
+        }
+
+        Ok::<_, std::io::Error>(())
+    });
+
+    let heartbeat_sender = sender.clone();
+    let heartbeat_task = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(heartbeat_interval).await;
+
+            if heartbeat_sender.send(WorkerMessage::Heartbeat).is_err() {
+                break;
+            }
+        }
+    });
+
+    let obj_func = Arc::new(obj_func);
+    let free_slots = Arc::new(Mutex::new((0..capacity).collect::<BTreeSet<_>>()));
+    let abort_senders = Arc::new(Mutex::new(HashMap::new()));
+    let mut evaluations = JoinSet::new();
+    let mut lines = reader.lines();
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let line = match line {
+                    Ok(Some(line)) => line,
+                    _ => break,
+                };
+
+                match distributed::parse_message(&line) {
+                    Ok(CoordinatorMessage::Job(job)) => {
+                        // the coordinator counts cancelled jobs until they have ended, so
+                        // this only happens if it disregards the announced capacity
+                        let slot = match free_slots.lock().unwrap().pop_first() {
+                            Some(slot) => slot,
+                            None => {
+                                warn!("No free slot for job {}", job.id);
+                                sender
+                                    .send(WorkerMessage::Failed {
+                                        id: job.id,
+                                        message: "worker capacity exceeded".to_string(),
+                                    })
+                                    .ok();
+                                continue;
+                            }
+                        };
+
+                        let (abort_sender, abort_receiver) = async_broadcast::broadcast(1);
+                        abort_senders.lock().unwrap().insert(job.id, abort_sender);
+
+                        evaluations.spawn(evaluate(
+                            job,
+                            slot,
+                            obj_func.clone(),
+                            abort_receiver,
+                            free_slots.clone(),
+                            abort_senders.clone(),
+                            sender.clone(),
+                        ));
+                    }
+                    Ok(CoordinatorMessage::Cancel { id }) => {
+                        if let Some(abort_sender) = abort_senders.lock().unwrap().remove(&id) {
+                            abort_sender.try_broadcast(()).ok();
+                        }
+                    }
+                    Err(err) => warn!("{}", err),
+                }
+            }
+            Some(_) = evaluations.join_next() => (),
+        }
+    }
+
+    info!("Coordinator closed the connection");
+
+    for (_, abort_sender) in abort_senders.lock().unwrap().drain() {
+        abort_sender.try_broadcast(()).ok();
+    }
+
+    while evaluations.join_next().await.is_some() {}
+
+    heartbeat_task.abort();
+    drop(sender);
+    writer_task.await.ok();
+
+    Ok(())
+}
+
+async fn evaluate<F: AsyncObjectiveFunction + Send + 'static>(
+    job: Job,
+    slot: usize,
+    obj_func: Arc<F>,
+    abort_receiver: async_broadcast::Receiver<()>,
+    free_slots: Arc<Mutex<BTreeSet<usize>>>,
+    abort_senders: Arc<Mutex<HashMap<u64, async_broadcast::Sender<()>>>>,
+    sender: mpsc::UnboundedSender<WorkerMessage>,
+) {
+    let eval_ctx = EvalContext {
+        seed: job.seed,
+        budget: job.budget,
+        individual_id: job.individual_id,
+        sample_index: job.sample_index,
+        slot,
+        cpu_set: None,
+    };
+
+    // spawned separately, so that the slot is released and the job answered even on a panic
+    let evaluation = tokio::spawn(async move {
+        obj_func
+            .evaluate(
+                job.params,
+                abort_receiver,
+                &eval_ctx,
+                &IntermediateReporter::disabled(),
+            )
+            .await
+    });
+    let result = evaluation.await;
+
+    abort_senders.lock().unwrap().remove(&job.id);
+    free_slots.lock().unwrap().insert(slot);
+
+    let message = match result {
+        Ok(Ok(result)) => WorkerMessage::Result {
+            id: job.id,
+            result: result.into(),
+        },
+        Ok(Err(err)) => WorkerMessage::Failed {
+            id: job.id,
+            message: err.to_string(),
+        },
+        Err(join_error) => WorkerMessage::Failed {
+            id: job.id,
+            message: join_error.to_string(),
+        },
+    };
+
+    sender.send(message).ok();
+}
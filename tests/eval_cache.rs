@@ -0,0 +1,71 @@
+use cambrian::error::Error;
+use cambrian::meta::{self, AlgoConfigBuilder};
+use cambrian::spec_util;
+use cambrian::{sync_launch, termination::TerminationCriterion};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[test]
+fn duplicates_not_reevaluated() {
+    let spec_str = "
+    type: enum
+    init: foo
+    values:
+    - foo
+    - bar
+    - baz
+    ";
+
+    let spec = spec_util::from_yaml_str(spec_str).unwrap();
+
+    let num_calls = Arc::new(AtomicUsize::new(0));
+    let num_calls_in_obj_func = num_calls.clone();
+
+    let obj_func = meta::make_obj_func(move |value| {
+        num_calls_in_obj_func.fetch_add(1, Ordering::SeqCst);
+        match value.as_str().unwrap() {
+            "foo" => Some(1.0),
+            "bar" => Some(2.0),
+            _ => None,
+        }
+    });
+
+    let algo_config = AlgoConfigBuilder::new().eval_cache(true).build().unwrap();
+
+    let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(50)];
+
+    let result = sync_launch::launch(
+        spec,
+        obj_func,
+        algo_config,
+        termination_criteria,
+        None,
+        true,
+        None,
+    )
+    .unwrap();
+
+    let num_calls = num_calls.load(Ordering::SeqCst);
+
+    assert!(num_calls <= 3);
+    assert_eq!(result.num_eval_cache_misses, num_calls);
+    assert_eq!(result.num_eval_cache_hits, 50 - num_calls);
+    assert_eq!(
+        result.num_obj_func_eval_completed + result.num_obj_func_eval_rejected,
+        50
+    );
+    assert_eq!(result.best_seen.obj_func_val, 1.0);
+}
+
+#[test]
+fn stochastic_mode_requires_cache_file() {
+    let result = AlgoConfigBuilder::new()
+        .individual_sample_size(2)
+        .eval_cache(true)
+        .build();
+
+    assert!(matches!(
+        result.unwrap_err(),
+        Error::EvalCacheWithoutFileInStochasticMode
+    ));
+}
@@ -0,0 +1,127 @@
+use cambrian::meta::{AlgoConfigBuilder, ObjectiveFunction};
+use cambrian::spec_util;
+use cambrian::sync_launch::DetailedReportingFileInfo;
+use cambrian::{sync_launch, termination::TerminationCriterion};
+use std::sync::{Arc, Mutex};
+
+struct BudgetedObjFunc {
+    budgets_seen: Arc<Mutex<Vec<f64>>>,
+}
+
+impl ObjectiveFunction for BudgetedObjFunc {
+    fn evaluate(&self, _value: serde_json::Value) -> Option<f64> {
+        panic!("budget expected")
+    }
+
+    fn evaluate_with_budget(&self, value: serde_json::Value, budget: Option<f64>) -> Option<f64> {
+        let budget = budget.unwrap();
+        self.budgets_seen.lock().unwrap().push(budget);
+
+        let x = value.as_f64().unwrap();
+        Some(x * x + 10.0 / budget)
+    }
+}
+
+#[test]
+fn best_seen_at_full_budget() {
+    let spec_str = "
+    type: real
+    init: 5.0
+    scale: 1.0
+    ";
+
+    let spec = spec_util::from_yaml_str(spec_str).unwrap();
+
+    let budgets_seen = Arc::new(Mutex::new(Vec::new()));
+    let obj_func = BudgetedObjFunc {
+        budgets_seen: budgets_seen.clone(),
+    };
+
+    let algo_config = AlgoConfigBuilder::new()
+        .fidelity(1.0, 9.0, 3)
+        .build()
+        .unwrap();
+
+    let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(300)];
+
+    let result = sync_launch::launch(
+        spec,
+        obj_func,
+        algo_config,
+        termination_criteria,
+        None,
+        true,
+        None,
+    )
+    .unwrap();
+
+    let budgets_seen = budgets_seen.lock().unwrap();
+    let count_budget = |budget: f64| budgets_seen.iter().filter(|seen| **seen == budget).count();
+
+    assert_eq!(budgets_seen.len(), 300);
+    assert!(count_budget(1.0) > count_budget(3.0));
+    assert!(count_budget(3.0) > count_budget(9.0));
+    assert!(count_budget(9.0) > 0);
+
+    // lower budgets yield objective function values of at least 10 / 3
+    assert!(result.best_seen.obj_func_val < 10.0 / 3.0);
+    assert!(result.best_seen.obj_func_val >= 10.0 / 9.0);
+}
+
+// the optimum moves with the budget, so that low budget results are misleading
+struct MovingOptimumObjFunc;
+
+impl ObjectiveFunction for MovingOptimumObjFunc {
+    fn evaluate(&self, _value: serde_json::Value) -> Option<f64> {
+        panic!("budget expected")
+    }
+
+    fn evaluate_with_budget(&self, value: serde_json::Value, budget: Option<f64>) -> Option<f64> {
+        let x = value.as_f64().unwrap();
+        Some((x - 9.0 / budget.unwrap()).powi(2) - 10.0 / budget.unwrap())
+    }
+}
+
+#[test]
+fn best_seen_file_at_full_budget() {
+    let spec_str = "
+    type: real
+    init: 5.0
+    scale: 1.0
+    ";
+
+    let spec = spec_util::from_yaml_str(spec_str).unwrap();
+
+    let algo_config = AlgoConfigBuilder::new()
+        .fidelity(1.0, 9.0, 3)
+        .build()
+        .unwrap();
+
+    let out_dir =
+        std::env::temp_dir().join(format!("cambrian_multi_fidelity_{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let file_info = DetailedReportingFileInfo {
+        detailed_report_file_path: out_dir.join("detailed_report.csv"),
+        best_seen_file_path: out_dir.join("best_seen.json"),
+    };
+
+    let result = sync_launch::launch(
+        spec,
+        MovingOptimumObjFunc,
+        algo_config,
+        vec![TerminationCriterion::NumObjFuncEval(300)],
+        None,
+        true,
+        Some(&file_info),
+    )
+    .unwrap();
+
+    let best_seen: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file_info.best_seen_file_path).unwrap())
+            .unwrap();
+
+    std::fs::remove_dir_all(&out_dir).unwrap();
+
+    assert_eq!(best_seen, result.best_seen.value);
+}
@@ -1,18 +1,69 @@
 #[cfg(test)]
 mod tests {
+    use cambrian::config_template::{ConfigFileDef, ConfigTemplate};
+    use cambrian::control;
+    use cambrian::coordinator::Coordinator;
+    use cambrian::distributed::Endpoint;
     use cambrian::error::Error;
-    use cambrian::meta::AlgoConfigBuilder;
-    use cambrian::process::ObjFuncProcessDef;
+    use cambrian::message::{Command, Report, Response, Status};
+    use cambrian::meta::{
+        AlgoConfig, AlgoConfigBuilder, AsyncObjectiveFunction, EvalContext, ObjFuncResult,
+    };
+    use cambrian::output_archive::{OutputArchive, STDERR_FILE_NAME, STDOUT_FILE_NAME};
+    use cambrian::process::{ExitCodes, ObjFuncProcessDef, ResultSource};
+    use cambrian::pruning::IntermediateReporter;
     use cambrian::result::FinalReport;
-    use cambrian::{self, spec_util};
+    use cambrian::work_dir::WorkDirs;
+    use cambrian::{self, async_launch, spec_util, worker};
     use cambrian::{sync_launch, termination::TerminationCriterion};
+    use flate2::read::GzDecoder;
     use float_cmp::{approx_eq, assert_approx_eq};
-    use std::time::Duration;
+    use futures::StreamExt;
+    use std::ffi::OsString;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, Instant};
 
     fn run(
         script_name: &str,
         kill_obj_func_after: Duration,
         terminate_after: Duration,
+    ) -> Result<FinalReport, Error> {
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::TerminateAfter(terminate_after)];
+        run_with_algo_config(
+            script_name,
+            kill_obj_func_after,
+            algo_config,
+            termination_criteria,
+        )
+    }
+
+    fn run_with_algo_config(
+        script_name: &str,
+        kill_obj_func_after: Duration,
+        algo_config: AlgoConfig,
+        termination_criteria: Vec<TerminationCriterion>,
+    ) -> Result<FinalReport, Error> {
+        let obj_func = make_obj_func_def(script_name, vec![], kill_obj_func_after);
+        run_with_obj_func_def(obj_func, algo_config, termination_criteria)
+    }
+
+    fn make_obj_func_def(
+        script_name: &str,
+        args: Vec<OsString>,
+        kill_obj_func_after: Duration,
+    ) -> ObjFuncProcessDef {
+        let program_path = format!("{}/scripts/{}", env!("CARGO_MANIFEST_DIR"), script_name);
+        ObjFuncProcessDef::new(program_path.into(), args, Some(kill_obj_func_after))
+    }
+
+    fn run_with_obj_func_def(
+        obj_func: ObjFuncProcessDef,
+        algo_config: AlgoConfig,
+        termination_criteria: Vec<TerminationCriterion>,
     ) -> Result<FinalReport, Error> {
         let spec_str = "
         type: bool
@@ -21,15 +72,29 @@ mod tests {
 
         let spec = spec_util::from_yaml_str(spec_str).unwrap();
 
-        let program_path = format!("{}/scripts/{}", env!("CARGO_MANIFEST_DIR"), script_name);
-
-        let obj_func =
-            ObjFuncProcessDef::new(program_path.into(), vec![], Some(kill_obj_func_after));
+        sync_launch::launch_with_async_obj_func(
+            spec,
+            obj_func,
+            algo_config,
+            termination_criteria,
+            None,
+            false,
+            None,
+        )
+    }
 
+    fn run_with_reports(
+        script_name: &str,
+        kill_obj_func_after: Duration,
+        max_num_eval: usize,
+    ) -> (Result<FinalReport, Error>, Vec<Report>) {
+        let obj_func = make_obj_func_def(script_name, vec![], kill_obj_func_after);
         let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(max_num_eval)];
+        let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+        let mut reports = Vec::new();
 
-        let termination_criteria = vec![TerminationCriterion::TerminateAfter(terminate_after)];
-        sync_launch::launch_with_async_obj_func(
+        let result = sync_launch::launch_with_report_handler(
             spec,
             obj_func,
             algo_config,
@@ -37,7 +102,67 @@ mod tests {
             None,
             false,
             None,
-        )
+            |report| reports.push(report),
+        );
+
+        (result, reports)
+    }
+
+    #[test]
+    fn undrained_report_receiver() {
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_constraint.sh",
+            vec![],
+            Duration::from_millis(1000),
+        );
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+
+        let (_cmd_sender, cmd_recv) = futures::channel::mpsc::channel(1);
+        let (detailed_report_sender, detailed_report_recv) = futures::channel::mpsc::channel(1);
+        // held but only drained after the launch
+        let (report_sender, report_recv) = futures::channel::mpsc::unbounded();
+
+        let launch_fut = async_launch::launch_with_reports(
+            spec,
+            obj_func,
+            algo_config,
+            cmd_recv,
+            detailed_report_sender,
+            report_sender,
+            TerminationCriterion::NumObjFuncEval(300),
+            None,
+        );
+
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let detailed_reporting_fut = detailed_report_recv.collect::<Vec<_>>();
+                let launch_fut = async { futures::join!(launch_fut, detailed_reporting_fut).0 };
+                tokio::time::timeout(Duration::from_secs(10), launch_fut).await
+            })
+            .expect("launch stalled on the report channel")
+            .unwrap();
+
+        assert_eq!(
+            result.num_obj_func_eval_completed + result.num_obj_func_eval_infeasible,
+            300
+        );
+
+        // none of the events was dropped, although far more were sent than a bounded channel
+        // would have held
+        let reports: Vec<_> = futures::executor::block_on(report_recv.collect());
+        let num_started = reports
+            .iter()
+            .filter(|report| matches!(report, Report::IndividualEvalStarted { .. }))
+            .count();
+        assert_eq!(num_started, 300);
+        assert!(reports
+            .iter()
+            .any(|report| matches!(report, Report::NewBestSeen { .. })));
+        assert!(matches!(reports.last(), Some(Report::Terminated { .. })));
     }
 
     #[test]
@@ -75,6 +200,74 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn progress_events() {
+        let (result, reports) =
+            run_with_reports("mock_obj_func_sleep_250.sh", Duration::from_millis(1000), 2);
+        assert_eq!(result.unwrap().num_obj_func_eval_completed, 2);
+
+        assert_eq!(reports.len(), 6);
+        assert!(matches!(
+            reports[0],
+            Report::IndividualEvalStarted {
+                individual_id: 0,
+                seed: 0,
+                slot: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            reports[1],
+            Report::IndividualEvalCompleted {
+                individual_id: 0,
+                obj_func_val,
+                constraint_violation: None,
+                ..
+            } if obj_func_val == 0.1
+        ));
+        assert!(matches!(
+            reports[2],
+            Report::NewBestSeen {
+                individual_id: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            reports[3],
+            Report::IndividualEvalStarted {
+                individual_id: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            reports[4],
+            Report::IndividualEvalCompleted {
+                individual_id: 1,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &reports[5],
+            Report::Terminated {
+                termination_reason: Some(termination_reason)
+            } if termination_reason == "evals(2)"
+        ));
+    }
+
+    #[test]
+    fn timed_out_events() {
+        let (result, reports) =
+            run_with_reports("mock_obj_func_sleep_250.sh", Duration::from_millis(100), 2);
+        assert!(matches!(result.unwrap_err(), Error::NoIndividuals));
+
+        let num_timed_out = reports
+            .iter()
+            .filter(|report| matches!(report, Report::IndividualEvalTimedOut { .. }))
+            .count();
+        assert_eq!(num_timed_out, 2);
+        assert!(matches!(reports.last(), Some(Report::Terminated { .. })));
+    }
+
     #[test]
     fn completed_before_timeout() {
         let script_name = "mock_obj_func_sleep_250.sh";
@@ -93,4 +286,866 @@ mod tests {
         ));
         assert_approx_eq!(f64, obj_func_val, 0.1);
     }
+
+    #[test]
+    fn infeasible_value() {
+        let script_name = "mock_obj_func_constraint.sh";
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+        let result = run_with_algo_config(
+            script_name,
+            Duration::from_millis(1000),
+            algo_config,
+            termination_criteria,
+        )
+        .unwrap();
+
+        assert!(result.num_obj_func_eval_infeasible >= 1);
+        assert_eq!(
+            result.num_obj_func_eval_completed + result.num_obj_func_eval_infeasible,
+            10
+        );
+        assert_approx_eq!(f64, result.best_seen.obj_func_val, 0.5);
+        assert_eq!(result.best_seen.value, serde_json::Value::Bool(false));
+    }
+
+    #[test]
+    fn batch_evaluation() {
+        let script_name = "mock_obj_func_batch.sh";
+        let algo_config = AlgoConfigBuilder::new().batch_size(4).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+        let result = run_with_algo_config(
+            script_name,
+            Duration::from_millis(1000),
+            algo_config,
+            termination_criteria,
+        )
+        .unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 10);
+        assert_approx_eq!(f64, result.best_seen.obj_func_val, 0.0);
+    }
+
+    #[test]
+    fn invalid_batch_output() {
+        let script_name = "mock_obj_func_reject.sh";
+        let algo_config = AlgoConfigBuilder::new().batch_size(4).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+        let result = run_with_algo_config(
+            script_name,
+            Duration::from_millis(1000),
+            algo_config,
+            termination_criteria,
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ObjFuncProcInvalidOutput(_)
+        ));
+    }
+
+    #[test]
+    fn pruned_by_intermediate_reports() {
+        let script_name = "mock_obj_func_intermediate.sh";
+        let algo_config = AlgoConfigBuilder::new().pruning(0.5, 1).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(4)];
+        let result = run_with_algo_config(
+            script_name,
+            Duration::from_millis(1000),
+            algo_config,
+            termination_criteria,
+        )
+        .unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 1);
+        assert_eq!(result.num_obj_func_eval_rejected, 0);
+        assert_eq!(result.num_obj_func_eval_pruned, 3);
+        assert_approx_eq!(f64, result.best_seen.obj_func_val, 0.0);
+    }
+
+    #[test]
+    fn terminate_signal_before_kill() {
+        let cleanup_file_path =
+            std::env::temp_dir().join(format!("cambrian_cleanup_{}", std::process::id()));
+        fs::remove_file(&cleanup_file_path).ok();
+
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_cleanup.sh",
+            vec![cleanup_file_path.clone().into()],
+            Duration::from_millis(100),
+        );
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(1)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        assert!(matches!(result.unwrap_err(), Error::NoIndividuals));
+        assert_eq!(
+            fs::read_to_string(&cleanup_file_path).unwrap().trim(),
+            "cleaned up"
+        );
+        fs::remove_file(&cleanup_file_path).unwrap();
+    }
+
+    #[test]
+    fn kill_after_grace_period() {
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_ignore_term.sh",
+            vec![],
+            Duration::from_millis(100),
+        );
+        obj_func.kill_grace_period = Duration::from_millis(100);
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(1)];
+
+        let start_ts = Instant::now();
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        assert!(matches!(result.unwrap_err(), Error::NoIndividuals));
+        assert!(start_ts.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn cpu_time_limit_exceeded() {
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_cpu_hog.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.resource_limits.cpu_time_secs = Some(1);
+
+        let algo_config = AlgoConfigBuilder::new().num_concurrent(6).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(6)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        // the initial value exceeds the limit
+        assert!(result.num_obj_func_eval_limit_exceeded >= 1);
+        assert_eq!(
+            result.num_obj_func_eval_completed + result.num_obj_func_eval_limit_exceeded,
+            6
+        );
+        assert_eq!(result.best_seen.value, serde_json::Value::Bool(false));
+    }
+
+    #[test]
+    fn cpu_time_hard_limit_exceeded() {
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_ignore_xcpu.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.resource_limits.cpu_time_secs = Some(1);
+
+        let algo_config = AlgoConfigBuilder::new().num_concurrent(6).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(6)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        // the initial value exceeds the limit
+        assert!(result.num_obj_func_eval_limit_exceeded >= 1);
+        assert_eq!(
+            result.num_obj_func_eval_completed + result.num_obj_func_eval_limit_exceeded,
+            6
+        );
+    }
+
+    #[test]
+    fn crash_with_resource_limits() {
+        for signal in ["KILL", "SEGV"] {
+            let mut obj_func = make_obj_func_def(
+                "mock_obj_func_crash.sh",
+                vec![signal.into()],
+                Duration::from_millis(5000),
+            );
+            obj_func.resource_limits.cpu_time_secs = Some(10);
+            obj_func.resource_limits.address_space_bytes = Some(1_000_000_000);
+
+            let algo_config = AlgoConfigBuilder::new().build().unwrap();
+            let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(1)];
+            let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+            assert!(matches!(result.unwrap_err(), Error::ObjFuncProcFailed(_)));
+        }
+    }
+
+    #[test]
+    fn address_space_limit_exceeded() {
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_memory_hog.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.resource_limits.address_space_bytes = Some(100_000_000);
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        assert!(result.num_obj_func_eval_limit_exceeded >= 1);
+        assert_eq!(
+            result.num_obj_func_eval_completed + result.num_obj_func_eval_limit_exceeded,
+            10
+        );
+    }
+
+    fn make_work_dir_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("cambrian_{}_{}", name, std::process::id()));
+        fs::remove_dir_all(&root).ok();
+        root
+    }
+
+    #[test]
+    fn keep_best_work_dirs() {
+        let root = make_work_dir_root("keep_best_work_dirs");
+
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_work_dir.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.work_dirs = Some(WorkDirs::new(&root, Some(1), false).unwrap());
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+        assert_eq!(result.num_obj_func_eval_completed, 10);
+
+        let work_dirs: Vec<_> = fs::read_dir(&root).unwrap().collect();
+        assert_eq!(work_dirs.len(), 1);
+
+        let artifact_path = work_dirs[0].as_ref().unwrap().path().join("artifact.json");
+        assert_eq!(fs::read_to_string(artifact_path).unwrap().trim(), "true");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn keep_work_dirs_of_failed_evaluations() {
+        let root = make_work_dir_root("keep_work_dirs_of_failed_evaluations");
+
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_reject.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.work_dirs = Some(WorkDirs::new(&root, Some(0), false).unwrap());
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        assert!(matches!(result.unwrap_err(), Error::NoIndividuals));
+        assert_eq!(fs::read_dir(&root).unwrap().count(), 3);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn eval_context_env() {
+        let out_dir = "/tmp/cambrian_out";
+
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_env.sh",
+            vec![out_dir.into(), "extra".into()],
+            Duration::from_millis(5000),
+        );
+        obj_func.out_dir = Some(out_dir.into());
+        obj_func.env = vec![("EXTRA_VAR".into(), "extra".into())];
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(5)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 5);
+    }
+
+    #[test]
+    fn arg_placeholders() {
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_args.sh",
+            vec![
+                "--seed={seed}".into(),
+                "{params_file}".into(),
+                "--value={params}".into(),
+            ],
+            Duration::from_millis(5000),
+        );
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 3);
+    }
+
+    #[test]
+    fn unavailable_arg_placeholders() {
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_args.sh",
+            vec![
+                "--seed={seed}".into(),
+                "{params_file}".into(),
+                "--budget={budget}".into(),
+                "--id={individual_id}".into(),
+            ],
+            Duration::from_millis(5000),
+        );
+
+        let algo_config = AlgoConfigBuilder::new().batch_size(4).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::UnavailablePlaceholders(names) if names == ["seed", "budget", "individual_id"]
+        ));
+    }
+
+    #[test]
+    fn config_file_template() {
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_config.sh",
+            vec!["{config_file}".into()],
+            Duration::from_millis(5000),
+        );
+        obj_func.config_file = Some(ConfigFileDef {
+            template: ConfigTemplate::Text("seed = {seed}\nvalue = {params}\n".to_string()),
+            file_name: "config.ini".to_string(),
+        });
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 3);
+    }
+
+    #[test]
+    fn config_file_in_batch_mode() {
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_batch.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.config_file = Some(ConfigFileDef {
+            template: ConfigTemplate::Text("value = {params}\n".to_string()),
+            file_name: "config.ini".to_string(),
+        });
+
+        let algo_config = AlgoConfigBuilder::new().batch_size(4).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(8)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        assert!(matches!(result.unwrap_err(), Error::ConfigFileInBatchMode));
+    }
+
+    fn run_with_result_source(
+        script_name: &str,
+        args: Vec<OsString>,
+        result_source: ResultSource,
+    ) -> Result<FinalReport, Error> {
+        let mut obj_func = make_obj_func_def(script_name, args, Duration::from_millis(5000));
+        obj_func.result_source = result_source;
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        run_with_obj_func_def(obj_func, algo_config, termination_criteria)
+    }
+
+    #[test]
+    fn result_from_stdout_lines() {
+        let script_name = "mock_obj_func_noisy.sh";
+
+        let result = run_with_result_source(script_name, vec![], ResultSource::LastLine).unwrap();
+        assert_eq!(result.best_seen.obj_func_val, 0.1);
+
+        let result_source = ResultSource::PrefixedLine("CAMBRIAN_RESULT:".to_string());
+        let result = run_with_result_source(script_name, vec![], result_source).unwrap();
+        assert_eq!(result.best_seen.obj_func_val, 0.2);
+
+        assert!(matches!(
+            run_with_result_source(script_name, vec![], ResultSource::Stdout).unwrap_err(),
+            Error::ObjFuncProcInvalidOutput(_)
+        ));
+    }
+
+    #[test]
+    fn result_from_file() {
+        let result = run_with_result_source(
+            "mock_obj_func_result_file.sh",
+            vec!["{result_file}".into()],
+            ResultSource::File,
+        )
+        .unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 3);
+        assert_eq!(result.best_seen.obj_func_val, 0.3);
+    }
+
+    fn run_with_exit_codes(name: &str, max_retries: usize) -> Result<FinalReport, Error> {
+        let root = make_work_dir_root(name);
+
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_exit_code.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.work_dirs = Some(WorkDirs::new(&root, None, false).unwrap());
+        obj_func.exit_codes = ExitCodes {
+            reject: vec![3],
+            retry: vec![75],
+            max_retries,
+            ..ExitCodes::default()
+        };
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        fs::remove_dir_all(&root).unwrap();
+        result
+    }
+
+    #[test]
+    fn exit_codes() {
+        let result = run_with_exit_codes("exit_codes", 1).unwrap();
+
+        assert_eq!(result.num_obj_func_eval_completed, 5);
+        assert_eq!(result.num_obj_func_eval_rejected_by_exit_code, 5);
+        assert_eq!(result.num_obj_func_eval_retries, 5);
+    }
+
+    #[test]
+    fn retries_exhausted() {
+        let result = run_with_exit_codes("retries_exhausted", 0);
+        assert!(matches!(result.unwrap_err(), Error::ObjFuncProcFailed(_)));
+    }
+
+    #[test]
+    fn retry_does_not_read_result_file_of_previous_attempt() {
+        let root = make_work_dir_root("retry_result_file");
+
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_result_file_retry.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.work_dirs = Some(WorkDirs::new(&root, None, false).unwrap());
+        obj_func.result_source = ResultSource::File;
+        obj_func.exit_codes = ExitCodes {
+            retry: vec![75],
+            ..ExitCodes::default()
+        };
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(1)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::ObjFuncProcInvalidOutput(_)
+        ));
+    }
+
+    fn read_archived(log_dir: &Path, file_name: &str) -> String {
+        let mut result = String::new();
+        GzDecoder::new(fs::File::open(log_dir.join(file_name)).unwrap())
+            .read_to_string(&mut result)
+            .unwrap();
+        result
+    }
+
+    #[test]
+    fn archive_output() {
+        let root = make_work_dir_root("archive_output");
+
+        let mut obj_func = make_obj_func_def(
+            "mock_obj_func_noisy.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        obj_func.result_source = ResultSource::LastLine;
+        obj_func.output_archive = Some(OutputArchive::new(&root, Some(1), None, false).unwrap());
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(5)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+        assert_eq!(result.num_obj_func_eval_completed, 5);
+
+        let log_dirs: Vec<_> = fs::read_dir(&root).unwrap().collect();
+        assert_eq!(log_dirs.len(), 1);
+
+        let stdout = read_archived(&log_dirs[0].as_ref().unwrap().path(), STDOUT_FILE_NAME);
+        assert!(stdout.starts_with("loading model\n"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn archive_output_of_timed_out_evaluations() {
+        let root = make_work_dir_root("archive_output_of_timed_out_evaluations");
+
+        let mut obj_func =
+            make_obj_func_def("mock_obj_func_hang.sh", vec![], Duration::from_millis(200));
+        obj_func.output_archive = Some(OutputArchive::new(&root, None, None, false).unwrap());
+
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(2)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+        assert!(matches!(result.unwrap_err(), Error::NoIndividuals));
+
+        let log_dirs: Vec<_> = fs::read_dir(&root).unwrap().collect();
+        assert_eq!(log_dirs.len(), 2);
+
+        for log_dir in log_dirs {
+            let log_dir = log_dir.unwrap().path();
+            assert_eq!(read_archived(&log_dir, STDOUT_FILE_NAME), "loading model\n");
+            assert_eq!(read_archived(&log_dir, STDERR_FILE_NAME), "warming up\n");
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn distributed_evaluation() {
+        let coordinator = Coordinator::bind(
+            &Endpoint::from("127.0.0.1:0"),
+            Duration::from_millis(500),
+            None,
+        )
+        .unwrap();
+        let endpoint = coordinator.endpoint().clone();
+
+        // announces capacity but never sends heartbeats, so its jobs are reassigned
+        let mut silent_worker = TcpStream::connect(endpoint.to_string()).unwrap();
+        silent_worker
+            .write_all(b"{\"type\":\"hello\",\"capacity\":1}\n")
+            .unwrap();
+
+        let workers: Vec<_> = (0..2)
+            .map(|_| {
+                let endpoint = endpoint.clone();
+                let obj_func = make_obj_func_def(
+                    "mock_obj_func_constraint.sh",
+                    vec![],
+                    Duration::from_millis(5000),
+                );
+
+                std::thread::spawn(move || {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .unwrap()
+                        .block_on(worker::run(
+                            &endpoint,
+                            1,
+                            Duration::from_millis(100),
+                            obj_func,
+                        ))
+                })
+            })
+            .collect();
+
+        let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+        let algo_config = AlgoConfigBuilder::new().num_concurrent(3).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(10)];
+
+        let result = sync_launch::launch_with_async_obj_func(
+            spec,
+            coordinator,
+            algo_config,
+            termination_criteria,
+            None,
+            false,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            result.num_obj_func_eval_completed + result.num_obj_func_eval_infeasible,
+            10
+        );
+        assert_eq!(result.best_seen.obj_func_val, 0.5);
+
+        // workers stop when the coordinator goes away with the launch
+        for worker in workers {
+            worker.join().unwrap().unwrap();
+        }
+    }
+
+    #[test]
+    fn distributed_evaluation_timeout() {
+        let coordinator = Coordinator::bind(
+            &Endpoint::from("127.0.0.1:0"),
+            Duration::from_millis(500),
+            Some(Duration::from_millis(200)),
+        )
+        .unwrap();
+        let endpoint = coordinator.endpoint().clone();
+
+        // without a timeout of its own, the worker relies on the coordinator to cancel
+        let obj_func = make_obj_func_def("mock_obj_func_hang.sh", vec![], Duration::from_secs(60));
+        let worker = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(worker::run(
+                    &endpoint,
+                    1,
+                    Duration::from_millis(100),
+                    obj_func,
+                ))
+        });
+
+        let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(2)];
+
+        let start = Instant::now();
+        let result = sync_launch::launch_with_async_obj_func(
+            spec,
+            coordinator,
+            algo_config,
+            termination_criteria,
+            None,
+            false,
+            None,
+        );
+
+        assert!(matches!(result.unwrap_err(), Error::NoIndividuals));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        worker.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn worker_validates_obj_func() {
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_args.sh",
+            vec!["{config_file}".into()],
+            Duration::from_millis(5000),
+        );
+
+        // fails before connecting, there is no coordinator listening
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(worker::run(
+                &Endpoint::from("127.0.0.1:1"),
+                1,
+                Duration::from_millis(100),
+                obj_func,
+            ));
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::UnavailablePlaceholders(names) if names == ["config_file"]
+        ));
+    }
+
+    struct PanickingObjFunc;
+
+    #[async_trait::async_trait]
+    impl AsyncObjectiveFunction for PanickingObjFunc {
+        async fn evaluate(
+            &self,
+            _value: serde_json::Value,
+            _abort_signal_recv: async_broadcast::Receiver<()>,
+            _eval_ctx: &EvalContext,
+            _intermediate_reporter: &IntermediateReporter,
+        ) -> Result<ObjFuncResult, Error> {
+            panic!("evaluation panicked");
+        }
+    }
+
+    #[test]
+    fn distributed_evaluation_panic() {
+        let coordinator = Coordinator::bind(
+            &Endpoint::from("127.0.0.1:0"),
+            Duration::from_millis(500),
+            None,
+        )
+        .unwrap();
+        let endpoint = coordinator.endpoint().clone();
+
+        let worker = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(worker::run(
+                    &endpoint,
+                    1,
+                    Duration::from_millis(100),
+                    PanickingObjFunc,
+                ))
+        });
+
+        let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+        let algo_config = AlgoConfigBuilder::new().build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(2)];
+
+        // the job is answered rather than left pending forever
+        let result = sync_launch::launch_with_async_obj_func(
+            spec,
+            coordinator,
+            algo_config,
+            termination_criteria,
+            None,
+            false,
+            None,
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::WorkerEvaluationFailed(message) if message.contains("panicked")
+        ));
+        worker.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn distributed_pruning() {
+        let coordinator = Coordinator::bind(
+            &Endpoint::from("127.0.0.1:0"),
+            Duration::from_millis(500),
+            None,
+        )
+        .unwrap();
+
+        let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+        let algo_config = AlgoConfigBuilder::new().pruning(0.5, 1).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(2)];
+
+        let result = sync_launch::launch_with_async_obj_func(
+            spec,
+            coordinator,
+            algo_config,
+            termination_criteria,
+            None,
+            false,
+            None,
+        );
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::PruningInDistributedMode
+        ));
+    }
+
+    #[test]
+    fn control_socket() {
+        let socket_path =
+            std::env::temp_dir().join(format!("cambrian_control_{}.sock", std::process::id()));
+
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_sleep_250.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        let algo_config = AlgoConfigBuilder::new()
+            .control_socket(socket_path.clone())
+            .build()
+            .unwrap();
+        let termination_criteria = vec![TerminationCriterion::TerminateAfter(Duration::from_secs(
+            10,
+        ))];
+
+        let client = std::thread::spawn(move || {
+            let send = |command| control::send(&socket_path, &command).unwrap();
+            let status = || match send(Command::Status) {
+                Response::Status(status) => status,
+                response => panic!("unexpected response: {:?}", response),
+            };
+
+            let start = Instant::now();
+            while !socket_path.exists() && start.elapsed() < Duration::from_secs(5) {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+
+            let Status {
+                paused,
+                num_concurrent,
+                in_flight,
+                ..
+            } = status();
+            assert!(!paused);
+            assert_eq!(num_concurrent, 1);
+            assert_eq!(in_flight.len(), 1);
+
+            assert!(matches!(
+                send(Command::Inject { value: 3.into() }),
+                Response::Error { .. }
+            ));
+            assert!(matches!(
+                send(Command::Inject {
+                    value: false.into()
+                }),
+                Response::Ok
+            ));
+            assert!(matches!(
+                send(Command::SetNumConcurrent { num_concurrent: 2 }),
+                Response::Ok
+            ));
+            assert_eq!(status().in_flight.len(), 2);
+
+            assert!(matches!(send(Command::Pause), Response::Ok));
+            std::thread::sleep(Duration::from_millis(600));
+
+            let status = status();
+            assert!(status.paused);
+            assert!(status.in_flight.is_empty());
+            assert_eq!(status.best_seen_obj_func_val, Some(0.1));
+
+            assert!(matches!(send(Command::Terminate), Response::Ok));
+        });
+
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+        client.join().unwrap();
+
+        assert!(matches!(
+            result.termination_reason,
+            Some(TerminationCriterion::Signal)
+        ));
+    }
+
+    #[test]
+    fn cpu_pinning() {
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_nproc.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+
+        let algo_config = AlgoConfigBuilder::new().cpu_pinning(1, 0).build().unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria).unwrap();
+
+        // the objective function process reports the number of CPUs it may run on
+        assert_eq!(result.best_seen.obj_func_val, 1.0);
+    }
+
+    #[test]
+    fn not_enough_cpus_for_pinning() {
+        let obj_func = make_obj_func_def(
+            "mock_obj_func_nproc.sh",
+            vec![],
+            Duration::from_millis(5000),
+        );
+        let num_cpus = std::thread::available_parallelism().unwrap().get();
+
+        let algo_config = AlgoConfigBuilder::new()
+            .num_concurrent(num_cpus)
+            .cpu_pinning(1, 1)
+            .build()
+            .unwrap();
+        let termination_criteria = vec![TerminationCriterion::NumObjFuncEval(3)];
+        let result = run_with_obj_func_def(obj_func, algo_config, termination_criteria);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            Error::NotEnoughCpus { required, available } if required == num_cpus + 1 && available == num_cpus
+        ));
+    }
 }
@@ -1,15 +1,25 @@
 use cambrian::error::Error;
-use cambrian::meta::AlgoConfigBuilder;
+use cambrian::meta::{AlgoConfig, AlgoConfigBuilder};
 use cambrian::result::FinalReport;
+use cambrian::sync_launch;
+use cambrian::termination::{self, TerminationCriterion};
 use cambrian::{self, meta, spec_util};
-use cambrian::{sync_launch, termination::TerminationCriterion};
-use float_cmp::{approx_eq, assert_approx_eq};
+use float_cmp::assert_approx_eq;
 use std::thread;
 use std::time::Duration;
 
 fn run(
     termination_criteria: Vec<TerminationCriterion>,
     sleep_duration: Duration,
+) -> Result<FinalReport, Error> {
+    let algo_config = AlgoConfigBuilder::new().build().unwrap();
+    run_with_algo_config(termination_criteria, sleep_duration, algo_config)
+}
+
+fn run_with_algo_config(
+    termination_criteria: Vec<TerminationCriterion>,
+    sleep_duration: Duration,
+    algo_config: AlgoConfig,
 ) -> Result<FinalReport, Error> {
     let spec_str = "
     type: bool
@@ -23,9 +33,15 @@ fn run(
         Some(0.1)
     });
 
-    let algo_config = AlgoConfigBuilder::new().build().unwrap();
-
-    sync_launch::launch(spec, obj_func, algo_config, termination_criteria, None, true, None)
+    sync_launch::launch(
+        spec,
+        obj_func,
+        algo_config,
+        termination_criteria,
+        None,
+        true,
+        None,
+    )
 }
 
 #[test]
@@ -39,12 +55,7 @@ fn terminate_after() {
 
     assert_eq!(result.num_obj_func_eval_completed, 1);
     assert_eq!(result.num_obj_func_eval_rejected, 1);
-    assert!(approx_eq!(
-        f64,
-        result.processing_time.as_secs_f64(),
-        0.15,
-        epsilon = 25e-3
-    ));
+    assert!(result.processing_time >= Duration::from_millis(150));
     assert_approx_eq!(f64, obj_func_val, 0.1);
 }
 
@@ -55,6 +66,10 @@ fn max_num_obj_func_eval() {
     let obj_func_val = result.best_seen.obj_func_val;
     assert_eq!(result.num_obj_func_eval_completed, 11);
     assert_approx_eq!(f64, obj_func_val, 0.1);
+    assert_eq!(
+        result.termination_reason,
+        Some(TerminationCriterion::NumObjFuncEval(11))
+    );
 }
 
 #[test]
@@ -78,3 +93,104 @@ fn target_obj_func_val() {
     let result = result.unwrap();
     assert_eq!(result.num_obj_func_eval_completed, 1);
 }
+
+#[test]
+fn no_improvement() {
+    let termination_criteria = vec![
+        TerminationCriterion::NoImprovement {
+            evals: 5,
+            min_delta: 0.0,
+        },
+        TerminationCriterion::NumObjFuncEval(100),
+    ];
+
+    let result = run(termination_criteria, Duration::ZERO).unwrap();
+    assert_eq!(result.num_obj_func_eval_completed, 6);
+}
+
+#[test]
+fn no_improvement_for() {
+    let termination_criteria = vec![TerminationCriterion::NoImprovementFor(
+        Duration::from_millis(150),
+    )];
+
+    let result = run(termination_criteria, Duration::from_millis(100)).unwrap();
+
+    // the first evaluation completes after 100ms and is the only improvement
+    assert_eq!(result.num_obj_func_eval_completed, 2);
+    assert!(result.processing_time >= Duration::from_millis(250));
+}
+
+#[test]
+fn composite() {
+    let criterion = TerminationCriterion::All(vec![
+        TerminationCriterion::NumObjFuncEval(5),
+        TerminationCriterion::NoImprovement {
+            evals: 10,
+            min_delta: 0.0,
+        },
+    ]);
+
+    let termination_criteria = vec![criterion.clone(), TerminationCriterion::NumObjFuncEval(100)];
+    let result = run(termination_criteria, Duration::ZERO).unwrap();
+
+    assert_eq!(result.num_obj_func_eval_completed, 11);
+    assert_eq!(result.termination_reason, Some(criterion));
+}
+
+#[test]
+fn composite_from_expr() {
+    let criterion =
+        termination::parse_expr("evals(20) and (target(0.05) or no_improvement(3))").unwrap();
+
+    let result = run(vec![criterion], Duration::ZERO).unwrap();
+    assert_eq!(result.num_obj_func_eval_completed, 20);
+}
+
+#[test]
+fn drain_in_flight_evaluations() {
+    let termination_criteria = vec![TerminationCriterion::TerminateAfter(Duration::from_millis(
+        150,
+    ))];
+
+    let algo_config = AlgoConfigBuilder::new()
+        .drain_grace_period(Duration::from_secs(1))
+        .build()
+        .unwrap();
+
+    let result = run_with_algo_config(
+        termination_criteria,
+        Duration::from_millis(100),
+        algo_config,
+    )
+    .unwrap();
+
+    // the evaluation in flight at termination completes and is included
+    assert_eq!(result.num_obj_func_eval_completed, 2);
+    assert_eq!(result.num_obj_func_eval_rejected, 0);
+    assert!(result.processing_time >= Duration::from_millis(200));
+}
+
+#[test]
+fn drain_grace_period_elapsed() {
+    let termination_criteria = vec![TerminationCriterion::TerminateAfter(Duration::from_millis(
+        150,
+    ))];
+
+    let algo_config = AlgoConfigBuilder::new()
+        .drain_grace_period(Duration::from_millis(20))
+        .build()
+        .unwrap();
+
+    let result = run_with_algo_config(
+        termination_criteria,
+        Duration::from_millis(100),
+        algo_config,
+    )
+    .unwrap();
+
+    assert_eq!(result.num_obj_func_eval_completed, 1);
+    // aborted when the grace period ran out, before it would have completed
+    assert_eq!(result.num_obj_func_eval_rejected, 1);
+    assert!(result.processing_time >= Duration::from_millis(170));
+}
@@ -1,9 +1,12 @@
-use cambrian::meta::AlgoConfigBuilder;
+use cambrian::meta::{
+    AlgoConfig, AlgoConfigBuilder, MigrationTopology, ObjFuncResult, ObjectiveTransform,
+};
 use cambrian::result::FinalReport;
 use cambrian::{self, meta, spec_util};
 use cambrian::{sync_launch, termination::TerminationCriterion};
 use float_cmp::approx_eq;
 use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +18,15 @@ struct TestValue {
 fn get_result(
     explicit_init_value_json: Option<serde_json::Value>,
     termination_criteria: Vec<TerminationCriterion>,
+) -> FinalReport {
+    let algo_config = AlgoConfigBuilder::new().build().unwrap();
+    get_result_with_algo_config(algo_config, explicit_init_value_json, termination_criteria)
+}
+
+fn get_result_with_algo_config(
+    algo_config: AlgoConfig,
+    explicit_init_value_json: Option<serde_json::Value>,
+    termination_criteria: Vec<TerminationCriterion>,
 ) -> FinalReport {
     let spec_str = "
     x:
@@ -36,8 +48,6 @@ fn get_result(
         Some(x * x + y * y)
     });
 
-    let algo_config = AlgoConfigBuilder::new().build().unwrap();
-
     sync_launch::launch(
         spec,
         obj_func,
@@ -81,3 +91,230 @@ fn explicit_initial_value_equivalence() {
         result_implicit_init.best_seen.obj_func_val
     );
 }
+
+#[test]
+fn convergence_with_islands() {
+    let algo_config = AlgoConfigBuilder::new()
+        .num_islands(4)
+        .migration_interval(10)
+        .migration_topology(MigrationTopology::Random)
+        .build()
+        .unwrap();
+
+    let termination_criteria = vec![
+        TerminationCriterion::TargetObjFuncVal(1e-6),
+        TerminationCriterion::TerminateAfter(Duration::from_secs(1)),
+    ];
+
+    let result = get_result_with_algo_config(algo_config, None, termination_criteria);
+
+    assert!(approx_eq!(
+        f64,
+        result.best_seen.obj_func_val,
+        0.0,
+        epsilon = 1e-2
+    ));
+}
+
+#[test]
+fn convergence_with_batches() {
+    let algo_config = AlgoConfigBuilder::new()
+        .batch_size(8)
+        .num_concurrent(2)
+        .build()
+        .unwrap();
+
+    let termination_criteria = vec![
+        TerminationCriterion::TargetObjFuncVal(1e-6),
+        TerminationCriterion::TerminateAfter(Duration::from_secs(1)),
+    ];
+
+    let result = get_result_with_algo_config(algo_config, None, termination_criteria);
+
+    assert!(approx_eq!(
+        f64,
+        result.best_seen.obj_func_val,
+        0.0,
+        epsilon = 1e-2
+    ));
+}
+
+#[test]
+fn convergence_when_maximizing() {
+    let spec_str = "
+    x:
+        type: real
+        init: 1.0
+        scale: 0.1
+    ";
+
+    let spec = spec_util::from_yaml_str(spec_str).unwrap();
+
+    let obj_func = meta::make_obj_func(|value| {
+        let x = value["x"].as_f64().unwrap();
+        Some(10.0 - x * x)
+    });
+
+    let algo_config = AlgoConfigBuilder::new()
+        .maximize(true)
+        .objective_transform(ObjectiveTransform::Log)
+        .build()
+        .unwrap();
+
+    let termination_criteria = vec![
+        TerminationCriterion::TargetObjFuncVal(10.0 - 1e-6),
+        TerminationCriterion::TerminateAfter(Duration::from_secs(1)),
+    ];
+
+    let result = sync_launch::launch(
+        spec,
+        obj_func,
+        algo_config,
+        termination_criteria,
+        None,
+        true,
+        None,
+    )
+    .unwrap();
+
+    // the report shows the untransformed value
+    assert!(approx_eq!(
+        f64,
+        result.best_seen.obj_func_val,
+        10.0,
+        epsilon = 1e-2
+    ));
+}
+
+#[test]
+fn later_better_value_becomes_best_seen() {
+    let spec_str = "
+    x:
+        type: real
+        init: 1.0
+        scale: 0.1
+    ";
+
+    for objective_transform in [ObjectiveTransform::Log, ObjectiveTransform::Rank] {
+        let spec = spec_util::from_yaml_str(spec_str).unwrap();
+
+        // every evaluation is better than all previous ones
+        let num_calls = AtomicUsize::new(0);
+        let obj_func = meta::make_obj_func(move |_| {
+            Some((num_calls.fetch_add(1, Ordering::SeqCst) + 1) as f64)
+        });
+
+        let algo_config = AlgoConfigBuilder::new()
+            .maximize(true)
+            .objective_transform(objective_transform)
+            .build()
+            .unwrap();
+
+        let result = sync_launch::launch(
+            spec,
+            obj_func,
+            algo_config,
+            vec![TerminationCriterion::NumObjFuncEval(5)],
+            None,
+            true,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(result.best_seen.obj_func_val, 5.0);
+    }
+}
+
+#[test]
+fn log_transform_rejects_non_positive_values() {
+    let spec = spec_util::from_yaml_str("{type: real, init: 1.0, scale: 0.1}").unwrap();
+
+    // the first value is undefined for the log transform
+    let num_calls = AtomicUsize::new(0);
+    let obj_func =
+        meta::make_obj_func(move |_| Some(num_calls.fetch_add(1, Ordering::SeqCst) as f64));
+
+    let algo_config = AlgoConfigBuilder::new()
+        .objective_transform(ObjectiveTransform::Log)
+        .build()
+        .unwrap();
+
+    let result = sync_launch::launch(
+        spec,
+        obj_func,
+        algo_config,
+        vec![TerminationCriterion::NumObjFuncEval(5)],
+        None,
+        true,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(result.num_obj_func_eval_rejected, 1);
+    assert_eq!(result.num_obj_func_eval_completed, 4);
+    assert_eq!(result.best_seen.obj_func_val, 1.0);
+}
+
+#[test]
+fn in_process_evaluations_run_on_slot_cpus() {
+    // one reserved CPU and a slot of two
+    let num_cpus = std::thread::available_parallelism().unwrap().get();
+    if num_cpus < 3 {
+        return;
+    }
+
+    let spec = spec_util::from_yaml_str("{type: real, init: 1.0, scale: 0.1}").unwrap();
+
+    let obj_func =
+        meta::make_obj_func(|_| Some(std::thread::available_parallelism().unwrap().get() as f64));
+
+    let algo_config = AlgoConfigBuilder::new()
+        .num_concurrent(1)
+        .cpu_pinning(2, 1)
+        .build()
+        .unwrap();
+
+    let result = sync_launch::launch(
+        spec,
+        obj_func,
+        algo_config,
+        vec![TerminationCriterion::NumObjFuncEval(3)],
+        None,
+        true,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(result.best_seen.obj_func_val, 2.0);
+
+    // the calling thread gets its CPUs back
+    assert_eq!(
+        std::thread::available_parallelism().unwrap().get(),
+        num_cpus
+    );
+}
+
+#[test]
+fn constrained_obj_func() {
+    let spec = spec_util::from_yaml_str("{type: bool, init: true}").unwrap();
+
+    let obj_func = meta::make_constrained_obj_func(|value| match value.as_bool().unwrap() {
+        true => ObjFuncResult::infeasible(0.0, 1.0),
+        false => Some(1.0).into(),
+    });
+
+    let result = sync_launch::launch(
+        spec,
+        obj_func,
+        AlgoConfigBuilder::new().build().unwrap(),
+        vec![TerminationCriterion::NumObjFuncEval(5)],
+        None,
+        true,
+        None,
+    )
+    .unwrap();
+
+    // the initial value is infeasible, only the feasible one becomes best seen
+    assert!(result.num_obj_func_eval_infeasible >= 1);
+    assert_eq!(result.best_seen.obj_func_val, 1.0);
+}